@@ -0,0 +1,237 @@
+use std::ops::RangeInclusive;
+
+/// A parsed fenced code block info string.
+///
+/// Understands the common dialects:
+///
+/// - `rust,ignore` — language followed by comma-separated flags
+/// - `python title="app.py"` — language followed by `key=value` attributes
+/// - `{.js #main startline=3}` — Pandoc-style attribute blocks, where the
+///   first `.class` names the language
+/// - `rust {3,7-9}` — line numbers and ranges in braces name lines to
+///   emphasize; other bare words in braces become flags
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CodeInfo {
+    /// The language, e.g. `rust`. Empty if the info string names none.
+    pub language: String,
+    /// Bare words such as `ignore`, `no_run` or extra `.class` names.
+    pub flags: Vec<String>,
+    /// `key=value` pairs in the order they appear, with quotes removed.
+    /// A Pandoc `#id` is stored as `id`.
+    pub attributes: Vec<(String, String)>,
+    /// Line numbers and ranges listed in braces, e.g. `{3,7-9}`.
+    pub line_ranges: Vec<RangeInclusive<usize>>,
+}
+
+impl CodeInfo {
+    /// Parse a fenced code block info string.
+    pub fn parse(info: &str) -> Self {
+        let mut parsed = CodeInfo::default();
+
+        for (index, (token, in_braces)) in tokenize(info).into_iter().enumerate() {
+            if let Some((key, value)) = token.split_once('=') {
+                parsed
+                    .attributes
+                    .push((key.to_string(), unquote(value).to_string()));
+            } else if let Some(class) = token.strip_prefix('.') {
+                if parsed.language.is_empty() {
+                    parsed.language = class.to_string();
+                } else {
+                    parsed.flags.push(class.to_string());
+                }
+            } else if let Some(id) = token.strip_prefix('#').filter(|_| in_braces) {
+                parsed.attributes.push(("id".to_string(), id.to_string()));
+            } else if let Some(range) = parse_range(&token).filter(|_| in_braces) {
+                parsed.line_ranges.push(range);
+            } else if index == 0 && !in_braces {
+                parsed.language = token;
+            } else {
+                parsed.flags.push(token);
+            }
+        }
+
+        parsed
+    }
+
+    /// The value of the first attribute named `key`.
+    pub fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Whether `flag` appears as a bare word.
+    pub fn has_flag(&self, flag: &str) -> bool {
+        self.flags.iter().any(|f| f == flag)
+    }
+
+    /// Caption for the block, from a `title`, `filename` or `file` attribute.
+    pub fn title(&self) -> Option<&str> {
+        ["title", "filename", "file"]
+            .iter()
+            .find_map(|key| self.attribute(key))
+            .filter(|t| !t.is_empty())
+    }
+
+    /// Number of the block's first line, from a `startline` or Pandoc
+    /// `startFrom` attribute.
+    pub fn start_line(&self) -> Option<usize> {
+        ["startline", "startFrom"]
+            .iter()
+            .find_map(|key| self.attribute(key))
+            .and_then(|n| n.parse().ok())
+    }
+
+    /// Whether the block asks for a line-number gutter, via a `linenos` or
+    /// `numberLines` flag or a start line.
+    pub fn line_numbers(&self) -> bool {
+        self.has_flag("linenos") || self.has_flag("numberLines") || self.start_line().is_some()
+    }
+
+    /// Lines to emphasize, counted from 1 at the top of the block regardless
+    /// of [`start_line`](Self::start_line). Taken from
+    /// [`line_ranges`](Self::line_ranges) such as `{3,7-9}` and from an
+    /// `hl_lines="2 4"` attribute; bare numbers outside braces are flags.
+    pub fn emphasized_lines(&self) -> Vec<RangeInclusive<usize>> {
+        let hl_lines = self.attribute("hl_lines").unwrap_or("");
+        let hl_lines = hl_lines.split([' ', ',']).filter_map(parse_range);
+        self.line_ranges.iter().cloned().chain(hl_lines).collect()
+    }
+
+    /// Whether line `n` (1-based, see [`emphasized_lines`](Self::emphasized_lines))
+    /// is emphasized.
+    pub fn is_emphasized(&self, n: usize) -> bool {
+        self.emphasized_lines().iter().any(|r| r.contains(&n))
+    }
+}
+
+/// Parse `7` or `7-9` into a line range.
+fn parse_range(token: &str) -> Option<RangeInclusive<usize>> {
+    let (start, end) = token.split_once('-').unwrap_or((token, token));
+    let (start, end) = (start.parse().ok()?, end.parse().ok()?);
+    (start <= end).then_some(start..=end)
+}
+
+/// Split an info string into tokens, noting whether each was inside `{…}`.
+/// Whitespace and commas separate tokens except inside quotes.
+fn tokenize(info: &str) -> Vec<(String, bool)> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    let mut in_braces = false;
+
+    let mut finish = |current: &mut String, in_braces: bool| {
+        if !current.is_empty() {
+            tokens.push((std::mem::take(current), in_braces));
+        }
+    };
+
+    for c in info.trim().chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => {
+                quote = None;
+                current.push(c);
+            }
+            (Some(_), c) => current.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                current.push(c);
+            }
+            (None, '{') => {
+                finish(&mut current, in_braces);
+                in_braces = true;
+            }
+            (None, '}') => {
+                finish(&mut current, in_braces);
+                in_braces = false;
+            }
+            (None, c) if c == ',' || c.is_whitespace() => finish(&mut current, in_braces),
+            (None, c) => current.push(c),
+        }
+    }
+    finish(&mut current, in_braces);
+
+    tokens
+}
+
+fn unquote(value: &str) -> &str {
+    for q in ['"', '\''] {
+        if let Some(inner) = value.strip_prefix(q).and_then(|v| v.strip_suffix(q)) {
+            return inner;
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_language() {
+        let info = CodeInfo::parse("rust");
+        assert_eq!(info.language, "rust");
+        assert!(info.flags.is_empty());
+        assert!(info.attributes.is_empty());
+    }
+
+    #[test]
+    fn comma_flags() {
+        let info = CodeInfo::parse("rust,ignore,no_run");
+        assert_eq!(info.language, "rust");
+        assert!(info.has_flag("ignore"));
+        assert!(info.has_flag("no_run"));
+    }
+
+    #[test]
+    fn quoted_attributes() {
+        let info = CodeInfo::parse(r#"python title="my app.py" linenos"#);
+        assert_eq!(info.language, "python");
+        assert_eq!(info.title(), Some("my app.py"));
+        assert!(info.has_flag("linenos"));
+    }
+
+    #[test]
+    fn pandoc_attribute_block() {
+        let info = CodeInfo::parse("{.js .numberLines #main startFrom=10}");
+        assert_eq!(info.language, "js");
+        assert!(info.has_flag("numberLines"));
+        assert_eq!(info.attribute("id"), Some("main"));
+        assert_eq!(info.attribute("startFrom"), Some("10"));
+    }
+
+    #[test]
+    fn braces_after_language() {
+        let info = CodeInfo::parse("rust {3,7-9} filename=lib.rs");
+        assert_eq!(info.language, "rust");
+        assert!(info.flags.is_empty());
+        assert_eq!(info.line_ranges, vec![3..=3, 7..=9]);
+        assert_eq!(info.title(), Some("lib.rs"));
+    }
+
+    #[test]
+    fn bare_numbers_are_not_line_ranges() {
+        let info = CodeInfo::parse("rust,2 {.no_run}");
+        assert!(info.has_flag("2"));
+        assert!(info.has_flag("no_run"));
+        assert!(info.emphasized_lines().is_empty());
+    }
+
+    #[test]
+    fn line_ranges_and_start_line() {
+        let info = CodeInfo::parse(r#"rust {3,7-9} hl_lines="12 14-15" startline=120"#);
+        assert_eq!(info.emphasized_lines(), vec![3..=3, 7..=9, 12..=12, 14..=15]);
+        assert!(info.is_emphasized(8));
+        assert!(!info.is_emphasized(10));
+        assert_eq!(info.start_line(), Some(120));
+        assert!(info.line_numbers());
+        assert!(!CodeInfo::parse("rust,ignore").line_numbers());
+    }
+
+    #[test]
+    fn language_may_start_with_hash() {
+        assert_eq!(CodeInfo::parse("c#").language, "c#");
+        assert_eq!(CodeInfo::parse("").language, "");
+    }
+}
@@ -0,0 +1,227 @@
+use ratatui::prelude::*;
+
+/// How many colors the target terminal can display.
+///
+/// Rendered output is quantized to the nearest color the terminal supports,
+/// so truecolor syntax themes still look reasonable over SSH or in terminals
+/// limited to 256 or 16 colors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorDepth {
+    /// 24-bit RGB; colors are emitted unchanged.
+    #[default]
+    TrueColor,
+    /// The xterm 256-color palette.
+    Ansi256,
+    /// The 16 basic ANSI colors.
+    Ansi16,
+    /// No colors at all; only text modifiers are kept.
+    None,
+}
+
+/// RGB values of the 16 basic colors, as rendered by xterm.
+const ANSI16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// Channel values of the 6×6×6 color cube in the 256-color palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl ColorDepth {
+    /// Guess the color depth of the current terminal from `NO_COLOR`,
+    /// `COLORTERM` and `TERM`.
+    ///
+    /// This is opt-in: the renderer never inspects the environment on its own.
+    pub fn detect() -> Self {
+        if std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+            return ColorDepth::None;
+        }
+        let colorterm = std::env::var("COLORTERM").ok();
+        let term = std::env::var("TERM").ok();
+        Self::from_env(colorterm.as_deref(), term.as_deref())
+    }
+
+    /// Guess the color depth from the values of `COLORTERM` and `TERM`.
+    pub fn from_env(colorterm: Option<&str>, term: Option<&str>) -> Self {
+        let truecolor =
+            |c: &str| c.eq_ignore_ascii_case("truecolor") || c.eq_ignore_ascii_case("24bit");
+        if colorterm.is_some_and(truecolor) {
+            return ColorDepth::TrueColor;
+        }
+        match term {
+            Some("dumb") => ColorDepth::None,
+            Some(t) if t.contains("truecolor") || t.contains("direct") => ColorDepth::TrueColor,
+            Some(t) if t.contains("256color") => ColorDepth::Ansi256,
+            _ => ColorDepth::Ansi16,
+        }
+    }
+
+    /// Map `color` to the nearest color available at this depth, or `None`
+    /// if colors are disabled.
+    pub fn convert(self, color: Color) -> Option<Color> {
+        match self {
+            ColorDepth::TrueColor => Some(color),
+            ColorDepth::None => None,
+            ColorDepth::Ansi256 => Some(match color {
+                Color::Rgb(r, g, b) => Color::Indexed(nearest_256(r, g, b)),
+                other => other,
+            }),
+            ColorDepth::Ansi16 => Some(match color {
+                Color::Rgb(r, g, b) => nearest_16(r, g, b),
+                Color::Indexed(i) => {
+                    let (r, g, b) = indexed_to_rgb(i);
+                    nearest_16(r, g, b)
+                }
+                other => other,
+            }),
+        }
+    }
+
+    /// Apply [`ColorDepth::convert`] to the colors of a style.
+    pub fn apply_style(self, style: Style) -> Style {
+        if self == ColorDepth::TrueColor {
+            return style;
+        }
+        Style {
+            fg: style.fg.and_then(|c| self.convert(c)),
+            bg: style.bg.and_then(|c| self.convert(c)),
+            underline_color: style.underline_color.and_then(|c| self.convert(c)),
+            ..style
+        }
+    }
+
+    /// Convert every color in `lines` to this depth.
+    pub fn apply(self, lines: &mut [Line<'_>]) {
+        if self == ColorDepth::TrueColor {
+            return;
+        }
+        for line in lines {
+            line.style = self.apply_style(line.style);
+            for span in &mut line.spans {
+                span.style = self.apply_style(span.style);
+            }
+        }
+    }
+}
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let dr = r1 as i32 - r2 as i32;
+    let dg = g1 as i32 - g2 as i32;
+    let db = b1 as i32 - b2 as i32;
+    (dr * dr + dg * dg + db * db) as u32
+}
+
+fn nearest_16(r: u8, g: u8, b: u8) -> Color {
+    ANSI16
+        .iter()
+        .min_by_key(|(_, rgb)| distance(*rgb, (r, g, b)))
+        .map(|(color, _)| *color)
+        .unwrap_or(Color::Reset)
+}
+
+fn nearest_cube_index(v: u8) -> usize {
+    CUBE_LEVELS
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, level)| (**level as i32 - v as i32).abs())
+        .map(|(i, _)| i)
+        .unwrap_or(0)
+}
+
+fn nearest_256(r: u8, g: u8, b: u8) -> u8 {
+    let (ri, gi, bi) = (nearest_cube_index(r), nearest_cube_index(g), nearest_cube_index(b));
+    let cube = (CUBE_LEVELS[ri], CUBE_LEVELS[gi], CUBE_LEVELS[bi]);
+    let cube_index = 16 + 36 * ri + 6 * gi + bi;
+
+    // The grayscale ramp (232–255) runs from 8 to 238 in steps of 10.
+    let avg = (r as u32 + g as u32 + b as u32) / 3;
+    let gray_step = ((avg.saturating_sub(8) + 5) / 10).min(23) as u8;
+    let gray_value = 8 + gray_step * 10;
+    let gray = (gray_value, gray_value, gray_value);
+
+    if distance(gray, (r, g, b)) < distance(cube, (r, g, b)) {
+        232 + gray_step
+    } else {
+        cube_index as u8
+    }
+}
+
+fn indexed_to_rgb(i: u8) -> (u8, u8, u8) {
+    match i {
+        0..=15 => ANSI16[i as usize].1,
+        16..=231 => {
+            let i = i - 16;
+            (
+                CUBE_LEVELS[(i / 36) as usize],
+                CUBE_LEVELS[((i / 6) % 6) as usize],
+                CUBE_LEVELS[(i % 6) as usize],
+            )
+        }
+        _ => {
+            let v = 8 + (i - 232) * 10;
+            (v, v, v)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truecolor_is_unchanged() {
+        let c = Color::Rgb(12, 34, 56);
+        assert_eq!(ColorDepth::TrueColor.convert(c), Some(c));
+    }
+
+    #[test]
+    fn rgb_maps_to_256_palette() {
+        assert_eq!(ColorDepth::Ansi256.convert(Color::Rgb(255, 0, 0)), Some(Color::Indexed(196)));
+        assert_eq!(ColorDepth::Ansi256.convert(Color::Rgb(30, 30, 30)), Some(Color::Indexed(234)));
+        assert_eq!(ColorDepth::Ansi256.convert(Color::Cyan), Some(Color::Cyan));
+    }
+
+    #[test]
+    fn rgb_and_indexed_map_to_16_colors() {
+        assert_eq!(ColorDepth::Ansi16.convert(Color::Rgb(30, 30, 30)), Some(Color::Black));
+        assert_eq!(ColorDepth::Ansi16.convert(Color::Rgb(250, 10, 10)), Some(Color::LightRed));
+        assert_eq!(ColorDepth::Ansi16.convert(Color::Indexed(21)), Some(Color::Blue));
+    }
+
+    #[test]
+    fn none_strips_colors_but_keeps_modifiers() {
+        let style = Style::default()
+            .fg(Color::Red)
+            .bg(Color::Rgb(1, 2, 3))
+            .underline_color(Color::Blue)
+            .add_modifier(Modifier::BOLD);
+        let converted = ColorDepth::None.apply_style(style);
+        assert_eq!(converted.fg, None);
+        assert_eq!(converted.bg, None);
+        assert_eq!(converted.underline_color, None);
+        assert!(converted.add_modifier.contains(Modifier::BOLD));
+    }
+
+    #[test]
+    fn detects_depth_from_env_values() {
+        assert_eq!(ColorDepth::from_env(Some("truecolor"), Some("xterm")), ColorDepth::TrueColor);
+        assert_eq!(ColorDepth::from_env(None, Some("xterm-256color")), ColorDepth::Ansi256);
+        assert_eq!(ColorDepth::from_env(None, Some("xterm")), ColorDepth::Ansi16);
+        assert_eq!(ColorDepth::from_env(None, Some("dumb")), ColorDepth::None);
+        assert_eq!(ColorDepth::from_env(None, None), ColorDepth::Ansi16);
+    }
+}
@@ -0,0 +1,242 @@
+use ratatui::prelude::*;
+use ratatui::widgets::{StatefulWidgetRef, WidgetRef};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::links::{self, LinkDisplay};
+use crate::renderer::RenderOutput;
+use crate::search::SearchState;
+use crate::view::{self, MarkdownViewState};
+use crate::{Renderer, Theme};
+
+/// A markdown document that keeps its layout between draws.
+///
+/// Parsing and laying out happen on the first draw at a given width and are
+/// reused until the input changes, so redrawing every frame is cheap. A few
+/// widths are kept at once, so switching between layouts (e.g. with and
+/// without a scrollbar) does not re-render either. Draw it by reference with
+/// [`WidgetRef`], or with [`StatefulWidgetRef`] and a [`MarkdownViewState`]
+/// to scroll.
+///
+/// ```no_run
+/// # use ratatui::prelude::*;
+/// # use ratatui::widgets::StatefulWidgetRef;
+/// # use tui_md::{MarkdownDocument, MarkdownViewState};
+/// # fn draw(frame: &mut Frame, doc: &MarkdownDocument, state: &mut MarkdownViewState) {
+/// doc.render_ref(frame.area(), frame.buffer_mut(), state);
+/// # }
+/// ```
+pub struct MarkdownDocument {
+    input: String,
+    renderer: Renderer,
+    scrollbar: bool,
+    search: Option<SearchState>,
+    layouts: RefCell<HashMap<u16, Rc<RenderOutput>>>,
+}
+
+/// How many widths [`MarkdownDocument`] keeps a layout for before starting
+/// over, so resizing through many widths doesn't grow the cache unbounded.
+const MAX_LAYOUTS: usize = 4;
+
+impl MarkdownDocument {
+    pub fn new(input: &str) -> Self {
+        Self::with_renderer(input, Renderer::new())
+    }
+
+    /// Like [`MarkdownDocument::new`], but styled with a custom [`Theme`].
+    pub fn with_theme(input: &str, theme: &Theme) -> Self {
+        Self::with_renderer(input, Renderer::new().theme(theme.clone()))
+    }
+
+    /// Lay `input` out with a fully configured [`Renderer`]. Its width is
+    /// replaced by the width of the area the document is drawn into.
+    pub fn with_renderer(input: &str, renderer: Renderer) -> Self {
+        Self {
+            input: input.to_string(),
+            renderer,
+            scrollbar: false,
+            search: None,
+            layouts: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Show a vertical scrollbar when drawn with a [`MarkdownViewState`] and
+    /// the document is taller than the area.
+    pub fn scrollbar(mut self, enabled: bool) -> Self {
+        self.scrollbar = enabled;
+        self
+    }
+
+    /// Highlight the matches of `search` in the theme's search styles.
+    pub fn search(mut self, search: &SearchState) -> Self {
+        self.search = Some(search.clone());
+        self
+    }
+
+    /// Replace the search whose matches are highlighted, or stop
+    /// highlighting with `None`. The cached layout is kept.
+    pub fn set_search(&mut self, search: Option<&SearchState>) {
+        self.search = search.cloned();
+    }
+
+    /// The markdown source.
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    /// Replace the markdown source. The cached layout is kept if the text is
+    /// unchanged.
+    pub fn set_input(&mut self, input: &str) {
+        if self.input != input {
+            self.input = input.to_string();
+            self.layouts.get_mut().clear();
+        }
+    }
+
+    /// The document laid out at `width` columns, from the cache if possible.
+    pub fn output(&self, width: u16) -> Rc<RenderOutput> {
+        if let Some(output) = self.layouts.borrow().get(&width) {
+            return Rc::clone(output);
+        }
+        let output = Rc::new(self.renderer.render_output_at(&self.input, width));
+        let mut layouts = self.layouts.borrow_mut();
+        if layouts.len() >= MAX_LAYOUTS {
+            layouts.clear();
+        }
+        layouts.insert(width, Rc::clone(&output));
+        output
+    }
+
+    /// `output`'s lines with search matches and the focused link restyled,
+    /// or `None` when there is nothing to restyle and they can be drawn as is.
+    fn restyled_lines(
+        &self,
+        output: &RenderOutput,
+        focused_link: Option<usize>,
+    ) -> Option<Vec<Line<'static>>> {
+        if self.search.is_none() && focused_link.is_none() {
+            return None;
+        }
+        let theme = &self.renderer.theme;
+        let mut lines = output.lines.clone();
+        if let Some(search) = &self.search {
+            search.highlight(&mut lines, theme);
+        }
+        links::highlight_focused(&mut lines, &output.links, focused_link, theme);
+        Some(lines)
+    }
+
+    fn text_area(&self, area: Rect) -> Rect {
+        if self.scrollbar {
+            Rect {
+                width: area.width.saturating_sub(1),
+                ..area
+            }
+        } else {
+            area
+        }
+    }
+}
+
+impl WidgetRef for MarkdownDocument {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        let output = self.output(area.width);
+        let restyled = self.restyled_lines(&output, None);
+        let lines = restyled.as_deref().unwrap_or(&output.lines);
+        for (y, line) in (area.y..area.bottom()).zip(lines) {
+            buf.set_line(area.x, y, line, area.width);
+        }
+        if self.renderer.link_display == LinkDisplay::Hyperlink {
+            links::write_hyperlinks(buf, area, 0, &output.links);
+        }
+    }
+}
+
+impl StatefulWidgetRef for MarkdownDocument {
+    type State = MarkdownViewState;
+
+    fn render_ref(&self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let text_area = self.text_area(area);
+        let output = self.output(text_area.width);
+        let restyled = self.restyled_lines(&output, state.focused_link);
+        let lines = restyled.as_deref().unwrap_or(&output.lines);
+        view::render_lines(lines, self.scrollbar, area, text_area, buf, state);
+        if self.renderer.link_display == LinkDisplay::Hyperlink {
+            links::write_hyperlinks(buf, text_area, state.offset(), &output.links);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SearchQuery;
+
+    #[test]
+    fn layout_is_reused_until_width_or_input_changes() {
+        let mut doc = MarkdownDocument::new("Some words that will wrap at a narrow width.");
+        let wide = doc.output(80);
+        assert!(Rc::ptr_eq(&doc.output(80), &wide));
+
+        // Both widths can be held at once
+        let narrow = doc.output(10);
+        assert!(narrow.lines.len() > 1);
+        assert_eq!(wide.lines.len(), 1);
+        assert!(Rc::ptr_eq(&doc.output(80), &wide));
+
+        doc.set_input("Some words that will wrap at a narrow width.");
+        assert!(Rc::ptr_eq(&doc.output(80), &wide));
+        doc.set_input("Other text");
+        assert_eq!(doc.output(80).lines[0].spans[0].content, "Other text");
+    }
+
+    #[test]
+    fn renders_by_reference() {
+        let doc = MarkdownDocument::new("# Title\n\nBody");
+        let area = Rect::new(0, 0, 10, 3);
+        for _ in 0..2 {
+            let mut buf = Buffer::empty(area);
+            WidgetRef::render_ref(&doc, area, &mut buf);
+            let row: String = (0..10).map(|x| buf[(x, 2)].symbol()).collect();
+            assert_eq!(row.trim_end(), "Body");
+        }
+    }
+
+    #[test]
+    fn search_matches_are_highlighted() {
+        let mut doc = MarkdownDocument::new("find the needle");
+        let search = SearchState::new(&SearchQuery::new("needle"), &doc.output(20).lines);
+        doc.set_search(Some(&search));
+        let area = Rect::new(0, 0, 20, 1);
+        let match_bg = Theme::default().search_match.bg;
+
+        let mut buf = Buffer::empty(area);
+        WidgetRef::render_ref(&doc, area, &mut buf);
+        assert_eq!(buf[(9, 0)].bg, match_bg.unwrap());
+        assert_ne!(buf[(8, 0)].style().bg, match_bg);
+
+        let mut buf = Buffer::empty(area);
+        let mut state = MarkdownViewState::default();
+        StatefulWidgetRef::render_ref(&doc, area, &mut buf, &mut state);
+        assert_eq!(buf[(14, 0)].bg, match_bg.unwrap());
+
+        doc.set_search(None);
+        let mut buf = Buffer::empty(area);
+        WidgetRef::render_ref(&doc, area, &mut buf);
+        assert_ne!(buf[(9, 0)].style().bg, match_bg);
+    }
+
+    #[test]
+    fn hyperlinks_are_written_when_drawn() {
+        let renderer = Renderer::new().link_display(LinkDisplay::Hyperlink);
+        let doc = MarkdownDocument::with_renderer("Intro\n\n[docs](https://docs.rs)", renderer);
+        let area = Rect::new(0, 0, 20, 3);
+        let mut buf = Buffer::empty(area);
+        let mut state = MarkdownViewState::default();
+        StatefulWidgetRef::render_ref(&doc, area, &mut buf, &mut state);
+        assert_eq!(buf[(0, 2)].symbol(), "\x1b]8;;https://docs.rs\x1b\\docs\x1b]8;;\x1b\\");
+        assert!(buf[(3, 2)].skip);
+        assert_eq!(buf[(4, 2)].symbol(), " ");
+    }
+}
@@ -0,0 +1,73 @@
+use crate::links::LinkPosition;
+
+/// A footnote, as listed in
+/// [`RenderOutput::footnotes`](crate::RenderOutput::footnotes).
+///
+/// Footnotes are numbered in the order they are first referenced, so
+/// `footnotes[0]` is note 1. A viewer can jump from a reference (see
+/// [`footnote_at`]) to [`Footnote::line`] and back to any of
+/// [`Footnote::references`] with [`MarkdownViewState::reveal`].
+///
+/// [`MarkdownViewState::reveal`]: crate::MarkdownViewState::reveal
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Footnote {
+    /// The label from `[^label]`.
+    pub label: String,
+    /// Where each reference marker was drawn.
+    pub references: Vec<LinkPosition>,
+    /// Index of the note's first line in the notes section.
+    pub line: usize,
+}
+
+impl Footnote {
+    /// Whether a reference marker covers `column` of rendered line `line`.
+    pub fn contains(&self, line: usize, column: usize) -> bool {
+        self.references
+            .iter()
+            .any(|p| p.line == line && p.columns.contains(&column))
+    }
+}
+
+/// Index of the footnote whose reference marker is drawn at `column` of
+/// rendered line `line`.
+pub fn footnote_at(footnotes: &[Footnote], line: usize, column: usize) -> Option<usize> {
+    footnotes.iter().position(|note| note.contains(line, column))
+}
+
+/// `number` in superscript digits, e.g. `¹²`.
+pub(crate) fn superscript(number: usize) -> String {
+    const DIGITS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+    number
+        .to_string()
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .map(|d| DIGITS[d as usize])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn superscript_digits() {
+        assert_eq!(superscript(1), "¹");
+        assert_eq!(superscript(10), "¹⁰");
+        assert_eq!(superscript(234), "²³⁴");
+    }
+
+    #[test]
+    fn hit_tests_reference_markers() {
+        let note = Footnote {
+            label: "a".into(),
+            references: vec![LinkPosition {
+                line: 2,
+                columns: 5..6,
+            }],
+            line: 9,
+        };
+        let footnotes = [Footnote::default(), note];
+        assert_eq!(footnote_at(&footnotes, 2, 5), Some(1));
+        assert_eq!(footnote_at(&footnotes, 2, 6), None);
+    }
+}
@@ -0,0 +1,347 @@
+use std::fmt;
+
+/// Metadata at the top of a document, between `---` fences (YAML) or
+/// `+++` fences (TOML), as found in
+/// [`RenderOutput::front_matter`](crate::RenderOutput::front_matter).
+///
+/// Only the common subset is understood: scalar values, inline and block
+/// lists, one level of nesting (flattened to keys like `author.name`) and
+/// TOML `[tables]`. Anything else is kept as the raw text of the value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrontMatter {
+    /// Which fences the block was written between.
+    pub format: FrontMatterFormat,
+    /// Keys and values in the order they appear.
+    pub entries: Vec<(String, FrontMatterValue)>,
+}
+
+/// The syntax of a front matter block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrontMatterFormat {
+    /// YAML between `---` fences, closed by `---` or `...`.
+    Yaml,
+    /// TOML between `+++` fences.
+    Toml,
+}
+
+/// The value of a front matter key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrontMatterValue {
+    /// A scalar with quotes removed, or the raw text of anything not
+    /// understood. Block scalars (`|`) keep their line breaks.
+    Text(String),
+    /// The items of an inline `[a, b]` or block `- a` list.
+    List(Vec<String>),
+}
+
+/// How the renderer shows front matter.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FrontMatterDisplay {
+    /// Parse it but draw nothing.
+    #[default]
+    Hidden,
+    /// A header with the title, a byline of author and date, and tags.
+    Card,
+    /// Every key and value in a two-column table.
+    Table,
+}
+
+impl FrontMatter {
+    /// The value of `key`. Nested keys are joined with dots.
+    pub fn get(&self, key: &str) -> Option<&FrontMatterValue> {
+        self.entries.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    pub(crate) fn parse(source: &str, format: FrontMatterFormat) -> Self {
+        let entries = match format {
+            FrontMatterFormat::Yaml => parse_yaml(source),
+            FrontMatterFormat::Toml => parse_toml(source),
+        };
+        Self { format, entries }
+    }
+}
+
+impl FrontMatterValue {
+    /// The items of a list, or a text value split on commas, as is common
+    /// for `tags: a, b`.
+    pub fn items(&self) -> Vec<String> {
+        match self {
+            Self::Text(text) => split_items(text),
+            Self::List(items) => items.clone(),
+        }
+    }
+}
+
+impl fmt::Display for FrontMatterValue {
+    /// Text as is; lists joined with `, `.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Text(text) => f.write_str(text),
+            Self::List(items) => f.write_str(&items.join(", ")),
+        }
+    }
+}
+
+/// Split leading front matter off `input`, returning its format, the source
+/// between the fences and the rest of the document. The block opens with
+/// `---` (YAML) or `+++` (TOML) on the first line, followed by a line that
+/// isn't blank, and closes with the same fence, or `...` for YAML.
+pub(crate) fn split(input: &str) -> Option<(FrontMatterFormat, &str, &str)> {
+    let (first, rest) = input.split_once('\n')?;
+    let format = match first.trim_end() {
+        "---" => FrontMatterFormat::Yaml,
+        "+++" => FrontMatterFormat::Toml,
+        _ => return None,
+    };
+    if rest.lines().next().is_none_or(|line| line.trim().is_empty()) {
+        return None;
+    }
+
+    let mut end = 0;
+    for line in rest.split_inclusive('\n') {
+        let fence = line.trim_end();
+        let closes = match format {
+            FrontMatterFormat::Yaml => fence == "---" || fence == "...",
+            FrontMatterFormat::Toml => fence == "+++",
+        };
+        if closes {
+            return Some((format, &rest[..end], &rest[end + line.len()..]));
+        }
+        end += line.len();
+    }
+    None
+}
+
+// ── YAML ──
+
+fn parse_yaml(source: &str) -> Vec<(String, FrontMatterValue)> {
+    let mut entries: Vec<(String, FrontMatterValue)> = Vec::new();
+    // A top-level key with no inline value, waiting for a list or mapping
+    let mut parent: Option<String> = None;
+    let mut lines = source.lines().peekable();
+
+    while let Some(line) = lines.next() {
+        let content = strip_comment(line);
+        if content.trim().is_empty() {
+            continue;
+        }
+        let indented = content.starts_with([' ', '\t']);
+        let content = content.trim();
+
+        if indented && let Some(key) = &parent {
+            if let Some(item) = content.strip_prefix('-') {
+                let item = unquote(item.trim());
+                match entries.last_mut() {
+                    Some((k, FrontMatterValue::List(items))) if k == key => items.push(item),
+                    _ => entries.push((key.clone(), FrontMatterValue::List(vec![item]))),
+                }
+            } else if let Some((sub, value)) = content.split_once(':') {
+                let key = format!("{}.{}", key, sub.trim());
+                entries.push((key, yaml_value(value.trim())));
+            }
+            continue;
+        }
+
+        let Some((key, value)) = content.split_once(':') else {
+            continue;
+        };
+        let key = key.trim().to_string();
+        let value = value.trim();
+        parent = None;
+        match value {
+            "" => parent = Some(key),
+            "|" | "|-" | ">" | ">-" => {
+                // Block scalar: the indented lines that follow
+                let mut block = Vec::new();
+                let continues = |l: &&str| l.trim().is_empty() || l.starts_with(' ');
+                while let Some(next) = lines.next_if(continues) {
+                    block.push(next.trim());
+                }
+                let separator = if value.starts_with('|') { "\n" } else { " " };
+                let text = block.join(separator).trim().to_string();
+                entries.push((key, FrontMatterValue::Text(text)));
+            }
+            value => entries.push((key, yaml_value(value))),
+        }
+    }
+    entries
+}
+
+fn yaml_value(value: &str) -> FrontMatterValue {
+    match value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+        Some(items) => FrontMatterValue::List(split_items(items)),
+        None => FrontMatterValue::Text(unquote(value)),
+    }
+}
+
+/// `line` without a trailing ` # comment` outside quotes. A quote only opens
+/// where a value or item starts, so the apostrophe in `Don't` doesn't.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut previous = ' ';
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') if previous.is_whitespace() || "=:[{,".contains(previous) => {
+                quote = Some(c)
+            }
+            (Some(q), c) if c == q => quote = None,
+            (None, '#') if previous.is_whitespace() => return &line[..i],
+            _ => {}
+        }
+        previous = c;
+    }
+    line
+}
+
+// ── TOML ──
+
+fn parse_toml(source: &str) -> Vec<(String, FrontMatterValue)> {
+    let mut entries = Vec::new();
+    let mut table = String::new();
+    let mut lines = source.lines();
+
+    while let Some(line) = lines.next() {
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            table = name.trim().trim_matches('[').trim_matches(']').to_string();
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let mut key = unquote(key.trim());
+        if !table.is_empty() {
+            key = format!("{}.{}", table, key);
+        }
+
+        let mut value = value.trim().to_string();
+        if value.starts_with('[') {
+            // Arrays may continue over several lines
+            while !value.ends_with(']') {
+                let Some(next) = lines.next() else { break };
+                value.push(' ');
+                value.push_str(strip_comment(next).trim());
+            }
+            let items = value.trim_start_matches('[').trim_end_matches(']');
+            entries.push((key, FrontMatterValue::List(split_items(items))));
+        } else {
+            entries.push((key, FrontMatterValue::Text(unquote(&value))));
+        }
+    }
+    entries
+}
+
+// ── Shared ──
+
+/// Split on commas outside quotes, unquoting each item and dropping empty
+/// ones (e.g. after a trailing comma). A quote only opens at the start of an
+/// item.
+fn split_items(text: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+    for c in text.chars() {
+        match (quote, c) {
+            (None, '"' | '\'') if current.trim().is_empty() => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, ',') => {
+                items.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    items.push(current);
+    items
+        .iter()
+        .map(|item| unquote(item.trim()))
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+fn unquote(text: &str) -> String {
+    for quote in ['"', '\''] {
+        if text.len() >= 2
+            && let Some(inner) = text.strip_prefix(quote).and_then(|t| t.strip_suffix(quote))
+        {
+            return inner.replace("\\\"", "\"");
+        }
+    }
+    text.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(value: &str) -> FrontMatterValue {
+        FrontMatterValue::Text(value.to_string())
+    }
+
+    fn list(items: &[&str]) -> FrontMatterValue {
+        FrontMatterValue::List(items.iter().map(|s| s.to_string()).collect())
+    }
+
+    #[test]
+    fn parses_yaml() {
+        let source = "title: \"Hello: World\"\nauthor:\n  name: Ada\n  email: ada@example.com\n\
+                      tags:\n  - rust\n  - 'tui'\ndraft: false # not yet\n\
+                      keywords: [a, \"b, c\"]\nsummary: >\n  Two\n  lines\n";
+        let matter = FrontMatter::parse(source, FrontMatterFormat::Yaml);
+        assert_eq!(
+            matter.entries,
+            [
+                ("title".to_string(), text("Hello: World")),
+                ("author.name".to_string(), text("Ada")),
+                ("author.email".to_string(), text("ada@example.com")),
+                ("tags".to_string(), list(&["rust", "tui"])),
+                ("draft".to_string(), text("false")),
+                ("keywords".to_string(), list(&["a", "b, c"])),
+                ("summary".to_string(), text("Two lines")),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_toml() {
+        let source = "title = \"Release notes\"\ndate = 2024-05-01\n\
+                      tags = [\n  \"news\",\n  \"release\",\n]\n\n[author]\nname = \"Grace\"\n";
+        let matter = FrontMatter::parse(source, FrontMatterFormat::Toml);
+        assert_eq!(matter.get("title"), Some(&text("Release notes")));
+        assert_eq!(matter.get("date"), Some(&text("2024-05-01")));
+        assert_eq!(matter.get("tags"), Some(&list(&["news", "release"])));
+        assert_eq!(matter.get("author.name"), Some(&text("Grace")));
+    }
+
+    #[test]
+    fn splits_leading_blocks_only() {
+        let yaml = "---\ntitle: A\n---\nBody";
+        assert_eq!(split(yaml), Some((FrontMatterFormat::Yaml, "title: A\n", "Body")));
+        let toml = "+++\ntitle = 1\n+++\n";
+        assert_eq!(split(toml), Some((FrontMatterFormat::Toml, "title = 1\n", "")));
+        assert_eq!(split("---\n\ntitle: A\n---\n"), None);
+        assert_eq!(split("---\ntitle: A\n"), None);
+        assert_eq!(split("+++\ntitle = 1\n---\n"), None);
+        assert_eq!(split("Intro\n---\na: b\n---\n"), None);
+    }
+
+    #[test]
+    fn comments_after_apostrophes_are_stripped() {
+        let source = "title: Don't # x\nnote: 'a # b' # c\ntags: [don't, \"x, y\"]";
+        let matter = FrontMatter::parse(source, FrontMatterFormat::Yaml);
+        assert_eq!(matter.get("title"), Some(&text("Don't")));
+        assert_eq!(matter.get("note"), Some(&text("a # b")));
+        assert_eq!(matter.get("tags"), Some(&list(&["don't", "x, y"])));
+    }
+
+    #[test]
+    fn values_display_and_split() {
+        assert_eq!(list(&["a", "b"]).to_string(), "a, b");
+        assert_eq!(text("x, y").items(), ["x", "y"]);
+    }
+}
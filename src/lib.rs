@@ -1,6 +1,38 @@
+mod code_info;
+mod color;
+mod document;
+mod footnotes;
+mod front_matter;
+mod links;
+mod math;
+mod outline;
 mod renderer;
+mod search;
 mod syntax;
+mod theme;
+mod view;
 mod widget;
+mod wrap;
 
-pub use renderer::render;
+pub use code_info::CodeInfo;
+pub use color::ColorDepth;
+pub use document::MarkdownDocument;
+pub use footnotes::{Footnote, footnote_at};
+pub use front_matter::{FrontMatter, FrontMatterDisplay, FrontMatterFormat, FrontMatterValue};
+pub use links::{ActivatedLink, Link, LinkDisplay, LinkPosition, link_at, resolve_anchor};
+pub use outline::{Heading, TableOfContents, TableOfContentsState, section_at};
+pub use renderer::{
+    CodeBlockInfo, RenderOutput, Renderer, render, render_with_theme, render_with_width,
+};
+pub use search::{SearchMatch, SearchQuery, SearchState, highlight_matches};
+pub use syntax::{
+    CodeBackground, CodeOptions, Highlighter, PlainHighlighter, guess_language, highlight_code,
+    highlight_code_block,
+};
+#[cfg(feature = "syntect")]
+pub use syntax::{SyntaxTheme, Syntaxes, SyntectHighlighter};
+#[cfg(feature = "tree-sitter")]
+pub use syntax::TreeSitterHighlighter;
+pub use theme::Theme;
+pub use view::{MarkdownView, MarkdownViewState};
 pub use widget::MarkdownWidget;
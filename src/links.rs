@@ -0,0 +1,401 @@
+use ratatui::prelude::*;
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::Theme;
+use crate::search::restyle_columns;
+use crate::view::MarkdownViewState;
+
+/// A link and where its text was rendered, as listed in
+/// [`RenderOutput::links`](crate::RenderOutput::links).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Link {
+    /// The destination, e.g. `https://example.com` or `#section`.
+    pub url: String,
+    /// The optional title from `[text](url "title")`.
+    pub title: String,
+    /// The link's plain text, without markup.
+    pub text: String,
+    /// Where the link was drawn, one entry per rendered line it occupies.
+    pub positions: Vec<LinkPosition>,
+}
+
+/// Part of a link on one rendered line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkPosition {
+    /// Index into the rendered lines.
+    pub line: usize,
+    /// Display columns covered by the link text. A ` (url)` suffix or `[n]`
+    /// reference number drawn after it is not included.
+    pub columns: Range<usize>,
+}
+
+/// How the renderer shows where a link goes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LinkDisplay {
+    /// Follow the link text with ` (url)`. Links to an anchor in the
+    /// document, like `[setup](#installation)`, show only their text here
+    /// and in [`Numbered`](Self::Numbered).
+    #[default]
+    Suffix,
+    /// Show only the link text, and make it clickable in terminals that
+    /// support OSC 8 hyperlinks. The escape sequences are written when a
+    /// widget draws the document, so they never affect layout.
+    ///
+    /// The escapes need at least two cells to hold them, so a link whose
+    /// text is a single cell wide, or the one-cell piece of a link wrapped
+    /// onto the next line, is drawn as plain text.
+    Hyperlink,
+    /// Show only the link text.
+    Hidden,
+    /// Follow the link text with a reference number such as `[1]`, and list
+    /// the destinations under a "Links" heading at the end of the document.
+    /// Links to the same destination share a number. Each listed
+    /// destination is itself a link, after the document's own in
+    /// [`RenderOutput::links`](crate::RenderOutput::links).
+    Numbered,
+}
+
+/// A link the user followed, returned to the host application to open.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActivatedLink {
+    /// Index into the link table.
+    pub index: usize,
+    /// The link's destination.
+    pub url: String,
+}
+
+impl Link {
+    /// The anchor named by a link within the document, e.g. `installation`
+    /// for `[setup](#installation)`.
+    pub fn fragment(&self) -> Option<&str> {
+        self.url.strip_prefix('#')
+    }
+
+    /// Whether the link covers `column` of rendered line `line`.
+    pub fn contains(&self, line: usize, column: usize) -> bool {
+        self.positions
+            .iter()
+            .any(|p| p.line == line && p.columns.contains(&column))
+    }
+}
+
+/// Index of the link drawn at `column` of rendered line `line`.
+pub fn link_at(links: &[Link], line: usize, column: usize) -> Option<usize> {
+    links.iter().position(|link| link.contains(line, column))
+}
+
+/// The line of the anchor named by `fragment`, as in
+/// [`RenderOutput::anchors`](crate::RenderOutput::anchors). Percent-escapes
+/// are decoded, and case is ignored if nothing matches exactly, in which
+/// case the earliest matching anchor wins.
+pub fn resolve_anchor(anchors: &HashMap<String, usize>, fragment: &str) -> Option<usize> {
+    let fragment = percent_decode(fragment);
+    anchors.get(&fragment).copied().or_else(|| {
+        let lower = fragment.to_lowercase();
+        anchors
+            .iter()
+            .filter(|(name, _)| name.to_lowercase() == lower)
+            .map(|(_, &line)| line)
+            .min()
+    })
+}
+
+/// Decode `%XX` escapes, leaving malformed ones as they are.
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok());
+        match (bytes[i], hex.and_then(|h| u8::from_str_radix(h, 16).ok())) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Patch [`Theme::link_focused`] onto every position of the focused link.
+pub(crate) fn highlight_focused(
+    lines: &mut [Line<'static>],
+    links: &[Link],
+    focused: Option<usize>,
+    theme: &Theme,
+) {
+    let Some(link) = focused.and_then(|i| links.get(i)) else {
+        return;
+    };
+    for position in &link.positions {
+        if let Some(line) = lines.get_mut(position.line) {
+            restyle_columns(line, &position.columns, theme.link_focused);
+        }
+    }
+}
+
+/// Wrap the cells of every visible link in OSC 8 hyperlink escapes.
+/// `offset` is the index of the rendered line drawn at the top of `area`.
+///
+/// Ratatui measures a cell's symbol escapes and all, so each run of cells
+/// that share a style is written as one symbol on its first cell and the
+/// rest are marked as skipped. A single-cell run joins its neighbour; a link
+/// piece only one cell wide is left as plain text.
+pub(crate) fn write_hyperlinks(buf: &mut Buffer, area: Rect, offset: usize, links: &[Link]) {
+    let area = area.intersection(buf.area);
+    for link in links {
+        for position in &link.positions {
+            let Some(row) = position.line.checked_sub(offset) else {
+                continue;
+            };
+            if row >= usize::from(area.height) {
+                continue;
+            }
+            let y = area.y + row as u16;
+            let start = position.columns.start.min(usize::from(area.width)) as u16;
+            let end = position.columns.end.min(usize::from(area.width)) as u16;
+            for run in style_runs(buf, area.x + start..area.x + end, y) {
+                write_hyperlink(buf, run, y, &link.url);
+            }
+        }
+    }
+}
+
+/// Split the cells `xs` of row `y` into runs of one style, at least two
+/// cells long.
+fn style_runs(buf: &Buffer, xs: Range<u16>, y: u16) -> Vec<Range<u16>> {
+    let mut runs: Vec<Range<u16>> = Vec::new();
+    for x in xs {
+        match runs.last_mut() {
+            Some(run) if buf[(run.start, y)].style() == buf[(x, y)].style() => run.end = x + 1,
+            _ => runs.push(x..x + 1),
+        }
+    }
+
+    let mut merged: Vec<Range<u16>> = Vec::with_capacity(runs.len());
+    for run in runs {
+        match merged.last_mut() {
+            Some(last) if last.len() < 2 || run.len() < 2 => last.end = run.end,
+            _ => merged.push(run),
+        }
+    }
+    merged.retain(|run| run.len() >= 2);
+    merged
+}
+
+fn write_hyperlink(buf: &mut Buffer, xs: Range<u16>, y: u16, url: &str) {
+    let text: String = xs.clone().map(|x| buf[(x, y)].symbol()).collect();
+    buf[(xs.start, y)].set_symbol(&format!("\x1b]8;;{url}\x1b\\{text}\x1b]8;;\x1b\\"));
+    for x in xs.start + 1..xs.end {
+        buf[(x, y)].set_skip(true);
+    }
+}
+
+/// Link focus and hit-testing, for documents drawn by a
+/// [`MarkdownView`](crate::MarkdownView) or
+/// [`MarkdownDocument`](crate::MarkdownDocument). Key handling is left to the
+/// host: map Tab and Shift-Tab to [`focus_next_link`] and
+/// [`focus_previous_link`], Enter to [`activate_focused_link`], and mouse
+/// clicks to [`click`].
+///
+/// [`focus_next_link`]: MarkdownViewState::focus_next_link
+/// [`focus_previous_link`]: MarkdownViewState::focus_previous_link
+/// [`activate_focused_link`]: MarkdownViewState::activate_focused_link
+/// [`click`]: MarkdownViewState::click
+impl MarkdownViewState {
+    /// Index into the link table of the focused link.
+    pub fn focused_link(&self) -> Option<usize> {
+        self.focused_link
+    }
+
+    /// Focus link `index`, or nothing, scrolling it into view.
+    pub fn focus_link(&mut self, links: &[Link], index: Option<usize>) {
+        self.focused_link = index.filter(|&i| i < links.len());
+        let line = self
+            .focused_link
+            .and_then(|i| links[i].positions.first())
+            .map(|p| p.line);
+        if let Some(line) = line {
+            self.reveal(line);
+        }
+    }
+
+    /// Focus the next link that has a position, wrapping around. With no
+    /// link focused, starts from the top of the view.
+    pub fn focus_next_link(&mut self, links: &[Link]) {
+        let visible = |i: &usize| !links[*i].positions.is_empty();
+        let next = match self.focused_link {
+            Some(current) => (current + 1..links.len()).chain(0..=current).find(visible),
+            None => (0..links.len())
+                .filter(visible)
+                .find(|&i| links[i].positions[0].line >= self.offset())
+                .or_else(|| (0..links.len()).find(visible)),
+        };
+        self.focus_link(links, next);
+    }
+
+    /// Focus the previous link that has a position, wrapping around.
+    pub fn focus_previous_link(&mut self, links: &[Link]) {
+        let visible = |i: &usize| !links[*i].positions.is_empty();
+        let start = self.focused_link.unwrap_or(0);
+        let previous = (0..start).rev().chain((start..links.len()).rev()).find(visible);
+        self.focus_link(links, previous);
+    }
+
+    /// The focused link, for the host to open.
+    pub fn activate_focused_link(&self, links: &[Link]) -> Option<ActivatedLink> {
+        let index = self.focused_link?;
+        links.get(index).map(|link| ActivatedLink {
+            index,
+            url: link.url.clone(),
+        })
+    }
+
+    /// Follow a `#fragment` link: scroll its anchor to the top of the view.
+    /// Returns the anchor's line, or `None` if `url` leads elsewhere or names
+    /// an anchor that doesn't exist.
+    pub fn jump_to_anchor(&mut self, anchors: &HashMap<String, usize>, url: &str) -> Option<usize> {
+        let line = resolve_anchor(anchors, url.strip_prefix('#')?)?;
+        self.scroll_to(line);
+        Some(line)
+    }
+
+    /// The rendered line and column under terminal cell (`x`, `y`), using the
+    /// area of the most recent render.
+    pub fn position_at(&self, x: u16, y: u16) -> Option<(usize, usize)> {
+        let area = self.area;
+        if x < area.x || x >= area.right() || y < area.y || y >= area.bottom() {
+            return None;
+        }
+        let line = self.offset() + usize::from(y - area.y);
+        (line < self.content_height()).then_some((line, usize::from(x - area.x)))
+    }
+
+    /// Hit-test a mouse click at terminal cell (`x`, `y`): focus and activate
+    /// the link there, if any.
+    pub fn click(&mut self, links: &[Link], x: u16, y: u16) -> Option<ActivatedLink> {
+        let (line, column) = self.position_at(x, y)?;
+        let index = link_at(links, line, column)?;
+        self.focused_link = Some(index);
+        self.activate_focused_link(links)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(url: &str, line: usize, columns: Range<usize>) -> Link {
+        Link {
+            url: url.to_string(),
+            positions: vec![LinkPosition { line, columns }],
+            ..Default::default()
+        }
+    }
+
+    fn view() -> MarkdownViewState {
+        let mut state = MarkdownViewState::default();
+        state.set_heights(100, 10);
+        state.area = Rect::new(2, 1, 40, 10);
+        state
+    }
+
+    #[test]
+    fn tab_cycles_through_links() {
+        let links = [link("a", 0, 0..3), Link::default(), link("c", 50, 4..9)];
+        let mut state = view();
+        state.focus_next_link(&links);
+        assert_eq!(state.focused_link(), Some(0));
+        state.focus_next_link(&links);
+        assert_eq!(state.focused_link(), Some(2));
+        assert!(state.offset() <= 50 && 50 < state.offset() + 10);
+        state.focus_next_link(&links);
+        assert_eq!(state.focused_link(), Some(0));
+        state.focus_previous_link(&links);
+        assert_eq!(state.focused_link(), Some(2));
+        let activated = state.activate_focused_link(&links).unwrap();
+        assert_eq!((activated.index, activated.url.as_str()), (2, "c"));
+    }
+
+    #[test]
+    fn fragments_resolve_to_anchors() {
+        let anchors = HashMap::from([("getting-started".to_string(), 40), ("über".to_string(), 7)]);
+        let mut state = view();
+        assert_eq!(state.jump_to_anchor(&anchors, "#getting-started"), Some(40));
+        assert_eq!(state.offset(), 40);
+        assert_eq!(state.jump_to_anchor(&anchors, "#Getting-Started"), Some(40));
+        assert_eq!(state.jump_to_anchor(&anchors, "#%C3%BCber"), Some(7));
+        assert_eq!(state.jump_to_anchor(&anchors, "#missing"), None);
+        assert_eq!(state.jump_to_anchor(&anchors, "https://example.com"), None);
+        assert_eq!(state.offset(), 7);
+    }
+
+    #[test]
+    fn case_insensitive_matches_pick_the_earliest_anchor() {
+        let anchors = HashMap::from([
+            ("Api".to_string(), 30),
+            ("API".to_string(), 12),
+            ("api-1".to_string(), 5),
+        ]);
+        assert_eq!(resolve_anchor(&anchors, "aPi"), Some(12));
+        assert_eq!(resolve_anchor(&anchors, "Api"), Some(30));
+    }
+
+    #[test]
+    fn hyperlinks_are_written_per_style_run() {
+        let area = Rect::new(0, 0, 12, 2);
+        let mut buf = Buffer::empty(area);
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        buf.set_line(0, 1, &Line::from(vec![Span::raw("see "), Span::styled("docs", bold)]), 12);
+        let links = [link("https://a.b", 5, 4..8), link("x", 5, 0..1)];
+        write_hyperlinks(&mut buf, area, 4, &links);
+
+        assert_eq!(buf[(4, 1)].symbol(), "\x1b]8;;https://a.b\x1b\\docs\x1b]8;;\x1b\\");
+        assert!((5..8).all(|x| buf[(x, 1)].skip));
+        assert!(!buf[(8, 1)].skip);
+    }
+
+    #[test]
+    fn one_cell_links_are_left_plain() {
+        let area = Rect::new(0, 0, 8, 2);
+        let mut buf = Buffer::empty(area);
+        buf.set_line(0, 0, &Line::from("a bc"), 8);
+        buf.set_line(0, 1, &Line::from("d"), 8);
+        // A one-cell link, and a link wrapped with one cell on its second line
+        let wrapped = Link {
+            positions: vec![
+                LinkPosition {
+                    line: 0,
+                    columns: 2..4,
+                },
+                LinkPosition {
+                    line: 1,
+                    columns: 0..1,
+                },
+            ],
+            ..link("https://w", 0, 0..0)
+        };
+        write_hyperlinks(&mut buf, area, 0, &[link("https://a", 0, 0..1), wrapped]);
+
+        assert_eq!(buf[(0, 0)].symbol(), "a");
+        assert_eq!(buf[(2, 0)].symbol(), "\x1b]8;;https://w\x1b\\bc\x1b]8;;\x1b\\");
+        assert_eq!(buf[(0, 1)].symbol(), "d");
+        assert!(!buf[(1, 1)].skip);
+    }
+
+    #[test]
+    fn clicks_hit_test_links() {
+        let links = [link("a", 3, 5..10)];
+        let mut state = view();
+        state.scroll_to(1);
+        // Line 3 is the third row of the view, offset by the area origin.
+        assert_eq!(state.click(&links, 2 + 6, 1 + 2).map(|l| l.url), Some("a".into()));
+        assert_eq!(state.click(&links, 2 + 11, 1 + 2), None);
+        assert_eq!(state.click(&links, 0, 0), None);
+    }
+}
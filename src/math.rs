@@ -0,0 +1,721 @@
+use crate::wrap::text_width;
+
+/// Parsed TeX. Groups are flattened into the surrounding list, except where
+/// they are the argument of a command or script.
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Frac(Vec<Node>, Vec<Node>),
+    Sqrt(Option<Vec<Node>>, Vec<Node>),
+    Sup(Vec<Node>),
+    Sub(Vec<Node>),
+}
+
+/// Render inline TeX such as `x^2 + \alpha` on one line, with fractions as
+/// `a⁄b`. Returns `None` if the TeX uses anything outside the supported
+/// subset, so the caller can show it as written.
+pub(crate) fn inline(tex: &str) -> Option<String> {
+    Some(flat(&parse(tex)?))
+}
+
+/// Render display TeX as one or more rows, with fractions stacked over a
+/// rule. Returns `None` like [`inline`].
+pub(crate) fn display(tex: &str) -> Option<Vec<String>> {
+    let block = layout(&parse(tex)?);
+    Some(block.rows.into_iter().map(|row| row.trim_end().to_string()).collect())
+}
+
+// ── Parsing ──
+
+/// How deeply groups and arguments may nest before the TeX is given up on,
+/// so hostile input can't exhaust the stack.
+const MAX_DEPTH: usize = 64;
+
+fn parse(tex: &str) -> Option<Vec<Node>> {
+    parse_at(tex, 0)
+}
+
+fn parse_at(tex: &str, depth: usize) -> Option<Vec<Node>> {
+    let mut parser = Parser {
+        chars: tex.trim().chars().collect(),
+        pos: 0,
+        depth,
+    };
+    let nodes = parser.nodes()?;
+    // A `}` with no matching `{` stops `nodes` early
+    (parser.pos == parser.chars.len()).then_some(nodes)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    /// Levels of `nodes` and `argument` currently open.
+    depth: usize,
+}
+
+impl Parser {
+    /// Nodes up to the end of input or the `}` closing the current group.
+    fn nodes(&mut self) -> Option<Vec<Node>> {
+        self.descend()?;
+        let mut nodes = Vec::new();
+        while let Some(&c) = self.chars.get(self.pos) {
+            match c {
+                '}' => break,
+                '{' => nodes.extend(self.group()?),
+                '^' | '_' => {
+                    self.pos += 1;
+                    let argument = self.argument()?;
+                    nodes.push(if c == '^' {
+                        Node::Sup(argument)
+                    } else {
+                        Node::Sub(argument)
+                    });
+                }
+                '\\' => nodes.extend(self.command()?),
+                '&' | '#' | '%' | '~' => return None,
+                c if c.is_whitespace() => {
+                    self.skip_whitespace();
+                    push_text(&mut nodes, " ");
+                }
+                '-' => {
+                    self.pos += 1;
+                    push_text(&mut nodes, "−");
+                }
+                '\'' => {
+                    self.pos += 1;
+                    push_text(&mut nodes, "′");
+                }
+                c => {
+                    self.pos += 1;
+                    push_text(&mut nodes, &c.to_string());
+                }
+            }
+        }
+        self.depth -= 1;
+        Some(nodes)
+    }
+
+    /// A `{…}` group, with the cursor on the `{`.
+    fn group(&mut self) -> Option<Vec<Node>> {
+        self.pos += 1;
+        let nodes = self.nodes()?;
+        if self.chars.get(self.pos) != Some(&'}') {
+            return None;
+        }
+        self.pos += 1;
+        Some(nodes)
+    }
+
+    /// The argument of a command or script: a group, a command or one char.
+    fn argument(&mut self) -> Option<Vec<Node>> {
+        self.descend()?;
+        self.skip_whitespace();
+        let argument = match *self.chars.get(self.pos)? {
+            '{' => self.group(),
+            '\\' => self.command(),
+            '}' | '^' | '_' => None,
+            c => {
+                self.pos += 1;
+                Some(vec![Node::Text(c.to_string())])
+            }
+        };
+        self.depth -= 1;
+        argument
+    }
+
+    /// Text of a `{…}` argument taken literally, as for `\text{…}`.
+    fn literal_argument(&mut self) -> Option<String> {
+        self.skip_whitespace();
+        if self.chars.get(self.pos) != Some(&'{') {
+            return None;
+        }
+        let end = self.chars[self.pos..].iter().position(|&c| c == '}')? + self.pos;
+        let text = self.chars[self.pos + 1..end].iter().collect();
+        self.pos = end + 1;
+        Some(text)
+    }
+
+    fn command(&mut self) -> Option<Vec<Node>> {
+        self.pos += 1;
+        let name_len = self.chars[self.pos..]
+            .iter()
+            .take_while(|c| c.is_ascii_alphabetic())
+            .count();
+        if name_len == 0 {
+            // A control symbol such as `\{` or `\,`
+            let c = *self.chars.get(self.pos)?;
+            self.pos += 1;
+            let text = match c {
+                '{' | '}' | '$' | '%' | '&' | '#' | '_' => c.to_string(),
+                ',' | ':' | ';' | ' ' => " ".to_string(),
+                '!' => String::new(),
+                '|' => "‖".to_string(),
+                _ => return None,
+            };
+            return Some(vec![Node::Text(text)]);
+        }
+
+        let name: String = self.chars[self.pos..self.pos + name_len].iter().collect();
+        self.pos += name_len;
+
+        let text = match name.as_str() {
+            "frac" | "dfrac" | "tfrac" => {
+                let numerator = self.argument()?;
+                let denominator = self.argument()?;
+                return Some(vec![Node::Frac(numerator, denominator)]);
+            }
+            "sqrt" => {
+                let index = if self.chars.get(self.pos) == Some(&'[') {
+                    let end = self.chars[self.pos..].iter().position(|&c| c == ']')? + self.pos;
+                    let index: String = self.chars[self.pos + 1..end].iter().collect();
+                    self.pos = end + 1;
+                    Some(parse_at(&index, self.depth)?)
+                } else {
+                    None
+                };
+                return Some(vec![Node::Sqrt(index, self.argument()?)]);
+            }
+            "text" | "textrm" | "mbox" | "operatorname" | "mathrm" | "mathit" | "mathbf" => {
+                self.literal_argument()?
+            }
+            "mathbb" => self
+                .literal_argument()?
+                .chars()
+                .map(double_struck)
+                .collect::<Option<String>>()?,
+            "left" | "right" | "big" | "Big" | "bigg" | "Bigg" => {
+                // Sizing only: keep the delimiter that follows
+                return match *self.chars.get(self.pos)? {
+                    '.' => {
+                        self.pos += 1;
+                        Some(Vec::new())
+                    }
+                    _ => self.argument(),
+                };
+            }
+            "displaystyle" | "textstyle" | "limits" | "nolimits" => String::new(),
+            "quad" => "  ".to_string(),
+            "qquad" => "    ".to_string(),
+            name => symbol(name)?.to_string(),
+        };
+        Some(vec![Node::Text(text)])
+    }
+
+    /// Open one more level, or `None` past [`MAX_DEPTH`]. Any `None` abandons
+    /// the whole parse, so only successful levels need closing.
+    fn descend(&mut self) -> Option<()> {
+        self.depth += 1;
+        (self.depth <= MAX_DEPTH).then_some(())
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.get(self.pos).is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+}
+
+fn push_text(nodes: &mut Vec<Node>, text: &str) {
+    match nodes.last_mut() {
+        Some(Node::Text(last)) => last.push_str(text),
+        _ => nodes.push(Node::Text(text.to_string())),
+    }
+}
+
+fn symbol(name: &str) -> Option<&'static str> {
+    Some(match name {
+        // Greek
+        "alpha" => "α",
+        "beta" => "β",
+        "gamma" => "γ",
+        "delta" => "δ",
+        "epsilon" => "ϵ",
+        "varepsilon" => "ε",
+        "zeta" => "ζ",
+        "eta" => "η",
+        "theta" => "θ",
+        "vartheta" => "ϑ",
+        "iota" => "ι",
+        "kappa" => "κ",
+        "lambda" => "λ",
+        "mu" => "μ",
+        "nu" => "ν",
+        "xi" => "ξ",
+        "pi" => "π",
+        "varpi" => "ϖ",
+        "rho" => "ρ",
+        "varrho" => "ϱ",
+        "sigma" => "σ",
+        "varsigma" => "ς",
+        "tau" => "τ",
+        "upsilon" => "υ",
+        "phi" => "ϕ",
+        "varphi" => "φ",
+        "chi" => "χ",
+        "psi" => "ψ",
+        "omega" => "ω",
+        "Gamma" => "Γ",
+        "Delta" => "Δ",
+        "Theta" => "Θ",
+        "Lambda" => "Λ",
+        "Xi" => "Ξ",
+        "Pi" => "Π",
+        "Sigma" => "Σ",
+        "Upsilon" => "Υ",
+        "Phi" => "Φ",
+        "Psi" => "Ψ",
+        "Omega" => "Ω",
+        // Operators and relations
+        "times" => "×",
+        "cdot" => "·",
+        "div" => "÷",
+        "pm" => "±",
+        "mp" => "∓",
+        "ast" => "∗",
+        "star" => "⋆",
+        "circ" => "∘",
+        "bullet" => "•",
+        "leq" | "le" => "≤",
+        "geq" | "ge" => "≥",
+        "neq" | "ne" => "≠",
+        "ll" => "≪",
+        "gg" => "≫",
+        "approx" => "≈",
+        "equiv" => "≡",
+        "sim" => "∼",
+        "simeq" => "≃",
+        "cong" => "≅",
+        "propto" => "∝",
+        "in" => "∈",
+        "notin" => "∉",
+        "ni" => "∋",
+        "subset" => "⊂",
+        "subseteq" => "⊆",
+        "supset" => "⊃",
+        "supseteq" => "⊇",
+        "cup" => "∪",
+        "cap" => "∩",
+        "setminus" => "∖",
+        "land" | "wedge" => "∧",
+        "lor" | "vee" => "∨",
+        "neg" | "lnot" => "¬",
+        "oplus" => "⊕",
+        "otimes" => "⊗",
+        "perp" => "⊥",
+        "parallel" => "∥",
+        "mid" => "∣",
+        // Big operators
+        "sum" => "∑",
+        "prod" => "∏",
+        "coprod" => "∐",
+        "int" => "∫",
+        "iint" => "∬",
+        "iiint" => "∭",
+        "oint" => "∮",
+        "bigcup" => "⋃",
+        "bigcap" => "⋂",
+        // Arrows
+        "to" | "rightarrow" => "→",
+        "leftarrow" | "gets" => "←",
+        "leftrightarrow" => "↔",
+        "Rightarrow" | "implies" => "⇒",
+        "Leftarrow" => "⇐",
+        "Leftrightarrow" | "iff" => "⇔",
+        "mapsto" => "↦",
+        "uparrow" => "↑",
+        "downarrow" => "↓",
+        // Miscellaneous
+        "infty" => "∞",
+        "partial" => "∂",
+        "nabla" => "∇",
+        "forall" => "∀",
+        "exists" => "∃",
+        "nexists" => "∄",
+        "emptyset" | "varnothing" => "∅",
+        "angle" => "∠",
+        "triangle" => "△",
+        "degree" => "°",
+        "prime" => "′",
+        "hbar" => "ℏ",
+        "ell" => "ℓ",
+        "Re" => "ℜ",
+        "Im" => "ℑ",
+        "aleph" => "ℵ",
+        "ldots" | "dots" => "…",
+        "cdots" => "⋯",
+        "vdots" => "⋮",
+        "ddots" => "⋱",
+        "langle" => "⟨",
+        "rangle" => "⟩",
+        "lfloor" => "⌊",
+        "rfloor" => "⌋",
+        "lceil" => "⌈",
+        "rceil" => "⌉",
+        "lbrace" => "{",
+        "rbrace" => "}",
+        "vert" => "|",
+        "Vert" => "‖",
+        // Function names
+        "sin" => "sin",
+        "cos" => "cos",
+        "tan" => "tan",
+        "cot" => "cot",
+        "sec" => "sec",
+        "csc" => "csc",
+        "arcsin" => "arcsin",
+        "arccos" => "arccos",
+        "arctan" => "arctan",
+        "sinh" => "sinh",
+        "cosh" => "cosh",
+        "tanh" => "tanh",
+        "log" => "log",
+        "ln" => "ln",
+        "lg" => "lg",
+        "exp" => "exp",
+        "lim" => "lim",
+        "limsup" => "lim sup",
+        "liminf" => "lim inf",
+        "max" => "max",
+        "min" => "min",
+        "sup" => "sup",
+        "inf" => "inf",
+        "det" => "det",
+        "dim" => "dim",
+        "ker" => "ker",
+        "gcd" => "gcd",
+        "deg" => "deg",
+        "arg" => "arg",
+        "Pr" => "Pr",
+        "mod" | "bmod" => "mod",
+        _ => return None,
+    })
+}
+
+fn double_struck(c: char) -> Option<char> {
+    Some(match c {
+        'C' => 'ℂ',
+        'H' => 'ℍ',
+        'N' => 'ℕ',
+        'P' => 'ℙ',
+        'Q' => 'ℚ',
+        'R' => 'ℝ',
+        'Z' => 'ℤ',
+        'A'..='Z' => char::from_u32(0x1D538 + (c as u32 - 'A' as u32))?,
+        _ => return None,
+    })
+}
+
+fn superscript(c: char) -> Option<char> {
+    Some(match c {
+        '0' => '⁰',
+        '1' => '¹',
+        '2' => '²',
+        '3' => '³',
+        '4' => '⁴',
+        '5' => '⁵',
+        '6' => '⁶',
+        '7' => '⁷',
+        '8' => '⁸',
+        '9' => '⁹',
+        '+' => '⁺',
+        '−' => '⁻',
+        '=' => '⁼',
+        '(' => '⁽',
+        ')' => '⁾',
+        'a' => 'ᵃ',
+        'b' => 'ᵇ',
+        'c' => 'ᶜ',
+        'd' => 'ᵈ',
+        'e' => 'ᵉ',
+        'f' => 'ᶠ',
+        'g' => 'ᵍ',
+        'h' => 'ʰ',
+        'i' => 'ⁱ',
+        'j' => 'ʲ',
+        'k' => 'ᵏ',
+        'l' => 'ˡ',
+        'm' => 'ᵐ',
+        'n' => 'ⁿ',
+        'o' => 'ᵒ',
+        'p' => 'ᵖ',
+        'r' => 'ʳ',
+        's' => 'ˢ',
+        't' => 'ᵗ',
+        'u' => 'ᵘ',
+        'v' => 'ᵛ',
+        'w' => 'ʷ',
+        'x' => 'ˣ',
+        'y' => 'ʸ',
+        'z' => 'ᶻ',
+        'A' => 'ᴬ',
+        'B' => 'ᴮ',
+        'D' => 'ᴰ',
+        'E' => 'ᴱ',
+        'G' => 'ᴳ',
+        'H' => 'ᴴ',
+        'I' => 'ᴵ',
+        'J' => 'ᴶ',
+        'K' => 'ᴷ',
+        'L' => 'ᴸ',
+        'M' => 'ᴹ',
+        'N' => 'ᴺ',
+        'O' => 'ᴼ',
+        'P' => 'ᴾ',
+        'R' => 'ᴿ',
+        'T' => 'ᵀ',
+        'U' => 'ᵁ',
+        'V' => 'ⱽ',
+        'W' => 'ᵂ',
+        'α' => 'ᵅ',
+        'β' => 'ᵝ',
+        'γ' => 'ᵞ',
+        'δ' => 'ᵟ',
+        'θ' => 'ᶿ',
+        'φ' | 'ϕ' => 'ᵠ',
+        'χ' => 'ᵡ',
+        '′' => '′',
+        '∗' => '*',
+        _ => return None,
+    })
+}
+
+fn subscript(c: char) -> Option<char> {
+    Some(match c {
+        '0' => '₀',
+        '1' => '₁',
+        '2' => '₂',
+        '3' => '₃',
+        '4' => '₄',
+        '5' => '₅',
+        '6' => '₆',
+        '7' => '₇',
+        '8' => '₈',
+        '9' => '₉',
+        '+' => '₊',
+        '−' => '₋',
+        '=' => '₌',
+        '(' => '₍',
+        ')' => '₎',
+        'a' => 'ₐ',
+        'e' => 'ₑ',
+        'h' => 'ₕ',
+        'i' => 'ᵢ',
+        'j' => 'ⱼ',
+        'k' => 'ₖ',
+        'l' => 'ₗ',
+        'm' => 'ₘ',
+        'n' => 'ₙ',
+        'o' => 'ₒ',
+        'p' => 'ₚ',
+        'r' => 'ᵣ',
+        's' => 'ₛ',
+        't' => 'ₜ',
+        'u' => 'ᵤ',
+        'v' => 'ᵥ',
+        'x' => 'ₓ',
+        'β' => 'ᵦ',
+        'γ' => 'ᵧ',
+        'ρ' => 'ᵨ',
+        'φ' | 'ϕ' => 'ᵩ',
+        'χ' => 'ᵪ',
+        _ => return None,
+    })
+}
+
+// ── Flat rendering ──
+
+fn flat(nodes: &[Node]) -> String {
+    let mut out = String::new();
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Frac(numerator, denominator) => {
+                out.push_str(&parenthesize(flat(numerator)));
+                out.push('⁄');
+                out.push_str(&parenthesize(flat(denominator)));
+            }
+            Node::Sqrt(index, radicand) => {
+                match index.as_deref().map(flat).as_deref() {
+                    None => out.push('√'),
+                    Some("3") => out.push('∛'),
+                    Some("4") => out.push('∜'),
+                    Some(index) => {
+                        out.push_str(&script(index, superscript, '^'));
+                        out.push('√');
+                    }
+                }
+                out.push_str(&parenthesize(flat(radicand)));
+            }
+            Node::Sup(argument) => out.push_str(&script(&flat(argument), superscript, '^')),
+            Node::Sub(argument) => out.push_str(&script(&flat(argument), subscript, '_')),
+        }
+    }
+    out
+}
+
+/// `text` in super- or subscript characters, or after `marker` (in
+/// parentheses if longer than a char) when some character has no such form.
+fn script(text: &str, map: fn(char) -> Option<char>, marker: char) -> String {
+    let text = text.trim();
+    let mapped: Option<String> = text.chars().filter(|c| *c != ' ').map(map).collect();
+    mapped.unwrap_or_else(|| match text.chars().count() {
+        1 => format!("{}{}", marker, text),
+        _ => format!("{}({})", marker, text),
+    })
+}
+
+/// Wrap `text` in parentheses unless it is a single number, name or symbol.
+fn parenthesize(text: String) -> String {
+    let text = text.trim().to_string();
+    let atomic = text.chars().count() <= 1
+        || text.chars().all(char::is_alphanumeric)
+        || (text.starts_with('(') && text.ends_with(')'));
+    if atomic {
+        text
+    } else {
+        format!("({})", text)
+    }
+}
+
+// ── Stacked layout ──
+
+/// Rows of text drawn as a unit, all padded to the same width. `baseline`
+/// is the row that lines up with the text around it.
+struct Block {
+    rows: Vec<String>,
+    baseline: usize,
+    width: usize,
+}
+
+impl Block {
+    fn text(text: String) -> Self {
+        Self {
+            width: text_width(&text),
+            rows: vec![text],
+            baseline: 0,
+        }
+    }
+}
+
+/// Lay `nodes` out left to right, stacking fractions over a rule.
+fn layout(nodes: &[Node]) -> Block {
+    let mut blocks = Vec::new();
+    let mut pending = Vec::new();
+    for node in nodes {
+        if let Node::Frac(numerator, denominator) = node {
+            if !pending.is_empty() {
+                blocks.push(Block::text(flat(&std::mem::take(&mut pending))));
+            }
+            blocks.push(fraction(layout(numerator), layout(denominator)));
+        } else {
+            pending.push(node.clone());
+        }
+    }
+    if !pending.is_empty() || blocks.is_empty() {
+        blocks.push(Block::text(flat(&pending)));
+    }
+    beside(blocks)
+}
+
+fn fraction(numerator: Block, denominator: Block) -> Block {
+    let width = numerator.width.max(denominator.width);
+    let mut rows = Vec::new();
+    for row in numerator.rows.iter() {
+        rows.push(centered(row, numerator.width, width));
+    }
+    let baseline = rows.len();
+    rows.push("─".repeat(width));
+    for row in denominator.rows.iter() {
+        rows.push(centered(row, denominator.width, width));
+    }
+    Block {
+        rows,
+        baseline,
+        width,
+    }
+}
+
+/// `row`, `row_width` columns wide, padded to `width` with the text centered.
+fn centered(row: &str, row_width: usize, width: usize) -> String {
+    let left = (width - row_width) / 2;
+    let right = width - row_width - left;
+    format!("{}{}{}", " ".repeat(left), row, " ".repeat(right))
+}
+
+/// Join blocks side by side, lining up their baselines.
+fn beside(blocks: Vec<Block>) -> Block {
+    let above = blocks.iter().map(|b| b.baseline).max().unwrap_or(0);
+    let below = blocks
+        .iter()
+        .map(|b| b.rows.len() - b.baseline - 1)
+        .max()
+        .unwrap_or(0);
+    let mut rows = vec![String::new(); above + below + 1];
+    for block in &blocks {
+        let top = above - block.baseline;
+        for (i, row) in rows.iter_mut().enumerate() {
+            match i.checked_sub(top).and_then(|i| block.rows.get(i)) {
+                Some(text) => row.push_str(&centered(text, text_width(text), block.width)),
+                None => row.push_str(&" ".repeat(block.width)),
+            }
+        }
+    }
+    Block {
+        width: blocks.iter().map(|b| b.width).sum(),
+        rows,
+        baseline: above,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn symbols_and_scripts() {
+        assert_eq!(inline(r"x^2 + y_i^2 = r^{2n}").as_deref(), Some("x² + yᵢ² = r²ⁿ"));
+        assert_eq!(inline(r"\alpha \leq \beta \cdot \pi").as_deref(), Some("α ≤ β · π"));
+        assert_eq!(inline(r"\sum_{i=1}^{n} i").as_deref(), Some("∑ᵢ₌₁ⁿ i"));
+        assert_eq!(inline(r"x \in \mathbb{R}").as_deref(), Some("x ∈ ℝ"));
+        assert_eq!(inline(r"a-b").as_deref(), Some("a−b"));
+    }
+
+    #[test]
+    fn fractions_and_roots_inline() {
+        assert_eq!(inline(r"\frac{1}{2}").as_deref(), Some("1⁄2"));
+        assert_eq!(inline(r"\frac{a+b}{c}").as_deref(), Some("(a+b)⁄c"));
+        assert_eq!(inline(r"\sqrt{x^2+1}").as_deref(), Some("√(x²+1)"));
+        assert_eq!(inline(r"\sqrt[3]{8}").as_deref(), Some("∛8"));
+    }
+
+    #[test]
+    fn scripts_without_unicode_forms_fall_back_to_carets() {
+        assert_eq!(inline(r"x_{b}").as_deref(), Some("x_b"));
+        assert_eq!(inline(r"e^{i\pi}").as_deref(), Some("e^(iπ)"));
+    }
+
+    #[test]
+    fn deep_nesting_is_rejected() {
+        let deep = format!("{}x{}", "{".repeat(5000), "}".repeat(5000));
+        assert_eq!(inline(&deep), None);
+        assert_eq!(display(&deep), None);
+        assert_eq!(inline(&r"\frac".repeat(5000)), None);
+        let shallow = format!("{}x{}", "{".repeat(10), "}".repeat(10));
+        assert_eq!(inline(&shallow).as_deref(), Some("x"));
+    }
+
+    #[test]
+    fn unsupported_tex_is_rejected() {
+        assert_eq!(inline(r"\begin{matrix} a & b \end{matrix}"), None);
+        assert_eq!(inline(r"\frac{1}"), None);
+        assert_eq!(inline(r"x}"), None);
+        assert_eq!(inline(r"\unknown"), None);
+    }
+
+    #[test]
+    fn display_fractions_are_stacked() {
+        let rows = display(r"x = \frac{-b \pm \sqrt{b^2 - 4ac}}{2a}").unwrap();
+        assert_eq!(rows, ["    −b ± √(b² − 4ac)", "x = ────────────────", "           2a"]);
+        assert_eq!(display(r"E = mc^2").unwrap(), ["E = mc²"]);
+    }
+}
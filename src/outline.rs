@@ -0,0 +1,214 @@
+use ratatui::prelude::*;
+use ratatui::widgets::{List, ListItem, ListState};
+use std::collections::HashMap;
+
+use crate::Theme;
+use crate::view::MarkdownViewState;
+
+/// A heading and where it was rendered, as listed in
+/// [`RenderOutput::outline`](crate::RenderOutput::outline).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heading {
+    /// 1 for `#`, up to 6 for `######`.
+    pub level: usize,
+    /// The heading's plain text, without markup.
+    pub text: String,
+    /// The heading's anchor: the id given with `{#id}`, or else a
+    /// GitHub-style slug such as `getting-started`, unique within the
+    /// document.
+    pub slug: String,
+    /// Index of the heading's first line in the rendered lines.
+    pub line: usize,
+}
+
+/// Lowercase `text`, drop punctuation and turn spaces into hyphens, the way
+/// GitHub names heading anchors.
+pub(crate) fn slugify(text: &str) -> String {
+    text.trim()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            '-' | '_' => Some(c),
+            c if c.is_alphanumeric() => Some(c),
+            _ => None,
+        })
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// [`slugify`] `text`, adding `-1`, `-2`, … to slugs already in `seen`.
+pub(crate) fn unique_slug(text: &str, seen: &mut HashMap<String, usize>) -> String {
+    let base = slugify(text);
+    let mut slug = base.clone();
+    if let Some(&count) = seen.get(&base) {
+        let mut n = count;
+        loop {
+            n += 1;
+            slug = format!("{}-{}", base, n);
+            if !seen.contains_key(&slug) {
+                break;
+            }
+        }
+        seen.insert(base, n);
+    }
+    seen.insert(slug.clone(), 0);
+    slug
+}
+
+/// Index of the heading whose section contains `line`: the last heading at
+/// or above it.
+pub fn section_at(outline: &[Heading], line: usize) -> Option<usize> {
+    outline.iter().rposition(|h| h.line <= line)
+}
+
+/// A sidebar listing the headings of a document, indented by level.
+///
+/// The section in view of a [`MarkdownView`](crate::MarkdownView) is shown
+/// in [`Theme::toc_active`] and the selected entry is reversed; see
+/// [`TableOfContentsState`] to keep them in sync and to jump.
+pub struct TableOfContents<'a> {
+    outline: &'a [Heading],
+    theme: Theme,
+}
+
+impl<'a> TableOfContents<'a> {
+    pub fn new(outline: &'a [Heading]) -> Self {
+        Self {
+            outline,
+            theme: Theme::default(),
+        }
+    }
+
+    /// Style entries with `theme` instead of [`Theme::default()`].
+    pub fn theme(mut self, theme: &Theme) -> Self {
+        self.theme = theme.clone();
+        self
+    }
+}
+
+/// Selection and active section of a [`TableOfContents`].
+#[derive(Debug, Clone, Default)]
+pub struct TableOfContentsState {
+    active: Option<usize>,
+    list: ListState,
+}
+
+impl TableOfContentsState {
+    /// Index into the outline of the selected entry.
+    pub fn selected(&self) -> Option<usize> {
+        self.list.selected()
+    }
+
+    pub fn select(&mut self, index: Option<usize>) {
+        self.list.select(index);
+    }
+
+    /// Index into the outline of the section in view.
+    pub fn active(&self) -> Option<usize> {
+        self.active
+    }
+
+    pub fn select_next(&mut self, outline: &[Heading]) {
+        let next = self.selected().map_or(0, |i| i + 1);
+        if next < outline.len() {
+            self.list.select(Some(next));
+        }
+    }
+
+    pub fn select_previous(&mut self, outline: &[Heading]) {
+        let previous = self.selected().map_or(0, |i| i.saturating_sub(1));
+        if previous < outline.len() {
+            self.list.select(Some(previous));
+        }
+    }
+
+    /// Mark the section at the top of `view` as active.
+    pub fn follow(&mut self, outline: &[Heading], view: &MarkdownViewState) {
+        self.active = section_at(outline, view.offset());
+    }
+
+    /// Scroll `view` to the selected heading.
+    pub fn jump(&mut self, outline: &[Heading], view: &mut MarkdownViewState) {
+        if let Some(heading) = self.selected().and_then(|i| outline.get(i)) {
+            view.scroll_to(heading.line);
+            self.follow(outline, view);
+        }
+    }
+}
+
+impl StatefulWidget for TableOfContents<'_> {
+    type State = TableOfContentsState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let items: Vec<ListItem<'_>> = self
+            .outline
+            .iter()
+            .enumerate()
+            .map(|(i, heading)| {
+                let indent = "  ".repeat(heading.level.saturating_sub(1));
+                let style = if state.active == Some(i) {
+                    self.theme.toc_active
+                } else {
+                    Style::default()
+                };
+                ListItem::new(Line::styled(format!("{}{}", indent, heading.text), style))
+            })
+            .collect();
+
+        let list = List::new(items).highlight_style(Modifier::REVERSED);
+        StatefulWidget::render(list, area, buf, &mut state.list);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn heading(text: &str, line: usize) -> Heading {
+        Heading {
+            level: 1,
+            text: text.to_string(),
+            slug: slugify(text),
+            line,
+        }
+    }
+
+    #[test]
+    fn slugs_follow_github_rules() {
+        assert_eq!(slugify("Getting Started"), "getting-started");
+        assert_eq!(slugify("What's new in v2.0?"), "whats-new-in-v20");
+        assert_eq!(slugify("snake_case & kebab-case"), "snake_case--kebab-case");
+        assert_eq!(slugify("Über Café"), "über-café");
+    }
+
+    #[test]
+    fn duplicate_slugs_get_suffixes() {
+        let mut seen = HashMap::new();
+        let slugs: Vec<String> = ["Usage", "Usage", "Usage 1", "Usage"]
+            .iter()
+            .map(|t| unique_slug(t, &mut seen))
+            .collect();
+        assert_eq!(slugs, ["usage", "usage-1", "usage-1-1", "usage-2"]);
+    }
+
+    #[test]
+    fn active_section_follows_the_view() {
+        let outline = [heading("A", 0), heading("B", 10), heading("C", 30)];
+        let mut view = MarkdownViewState::default();
+        view.set_heights(100, 10);
+        let mut toc = TableOfContentsState::default();
+
+        view.scroll_to(15);
+        toc.follow(&outline, &view);
+        assert_eq!(toc.active(), Some(1));
+
+        toc.select_next(&outline);
+        toc.select_next(&outline);
+        toc.select_next(&outline);
+        toc.select_next(&outline);
+        assert_eq!(toc.selected(), Some(2));
+        toc.jump(&outline, &mut view);
+        assert_eq!(view.offset(), 30);
+        assert_eq!(toc.active(), Some(2));
+    }
+}
@@ -1,36 +1,167 @@
-use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+use pulldown_cmark::{
+    Alignment, BlockQuoteKind, CodeBlockKind, Event, Options, Parser, Tag, TagEnd,
+};
 use ratatui::prelude::*;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::code_info::CodeInfo;
+use crate::color::ColorDepth;
+use crate::footnotes::{self, Footnote};
+use crate::front_matter::{self, FrontMatter, FrontMatterDisplay, FrontMatterValue};
+use crate::links::{self, Link, LinkDisplay, LinkPosition};
+use crate::math;
+use crate::outline::{self, Heading};
+use crate::syntax::{self, CodeBackground, CodeOptions, Highlighter};
+#[cfg(feature = "syntect")]
+use crate::syntax::{SyntaxTheme, Syntaxes, SyntectHighlighter};
+use crate::theme::Theme;
+use crate::wrap;
+
+/// Everything produced by [`Renderer::render_output`]: the styled lines plus
+/// metadata about what ended up where.
+#[derive(Debug, Clone, Default)]
+pub struct RenderOutput {
+    /// The rendered document.
+    pub lines: Vec<Line<'static>>,
+    /// Every fenced or indented code block, in document order.
+    pub code_blocks: Vec<CodeBlockInfo>,
+    /// Every heading, in document order.
+    pub outline: Vec<Heading>,
+    /// Line of each heading anchor: its GitHub-style slug, or the id given
+    /// with `{#id}`. An anchor belongs to the first heading that takes it;
+    /// slugs generated later skip ids already given. See
+    /// [`RenderOutput::resolve_link`].
+    pub anchors: HashMap<String, usize>,
+    /// Every link, in document order, then the entries of the "Links" list
+    /// drawn by [`LinkDisplay::Numbered`].
+    pub links: Vec<Link>,
+    /// Every referenced footnote, numbered from 1 in reference order.
+    pub footnotes: Vec<Footnote>,
+    /// Metadata from a `---` or `+++` block at the top of the document.
+    pub front_matter: Option<FrontMatter>,
+}
 
-use crate::syntax;
+impl RenderOutput {
+    /// The line a `#fragment` link points at, or `None` if the link leads
+    /// elsewhere or its anchor doesn't exist.
+    pub fn resolve_link(&self, link: &Link) -> Option<usize> {
+        links::resolve_anchor(&self.anchors, link.fragment()?)
+    }
 
-const CODE_BG: Color = Color::Rgb(30, 30, 30);
+    /// Indices into [`RenderOutput::links`] of `#fragment` links whose
+    /// anchor doesn't exist.
+    pub fn broken_links(&self) -> Vec<usize> {
+        self.links
+            .iter()
+            .enumerate()
+            .filter(|(_, link)| link.fragment().is_some() && self.resolve_link(link).is_none())
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+/// Where a code block was rendered and which language it was highlighted as.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeBlockInfo {
+    /// Index of the block's first line in [`RenderOutput::lines`].
+    pub line: usize,
+    /// The parsed info string: language, flags and attributes.
+    pub info: CodeInfo,
+    /// Language guessed from the content when the fence named none and
+    /// [`Renderer::guess_languages`] is on.
+    pub inferred_language: Option<String>,
+}
 
 /// Core markdown renderer. Walks pulldown-cmark events and builds `Vec<Line>`.
+#[derive(Clone)]
 pub struct Renderer {
+    pub(crate) theme: Theme,
+    code_options: CodeOptions,
+    /// The backend set up by `syntax_theme` and `syntaxes`, kept so that
+    /// calling both configures one highlighter.
+    #[cfg(feature = "syntect")]
+    syntect: Option<SyntectHighlighter>,
+    guess_languages: bool,
+    width: Option<u16>,
+    pub(crate) link_display: LinkDisplay,
+    front_matter_display: FrontMatterDisplay,
+    math: bool,
+
     lines: Vec<Line<'static>>,
+    code_blocks: Vec<CodeBlockInfo>,
+    outline: Vec<Heading>,
+    anchors: HashMap<String, usize>,
+    slugs: HashMap<String, usize>,
     current_spans: Vec<Span<'static>>,
+    // Leading spans of `current_spans` that are container prefixes, not text
+    prefix_len: usize,
+    // Drop leading whitespace from the next text, which resumes the line
+    // after display math
+    trim_next_text: bool,
     style_stack: Vec<Style>,
 
     // Block state
+    // Level and plain text of the heading being rendered
+    heading: Option<(usize, String)>,
+    // Explicit `{#id}` of the heading being rendered
+    heading_id: Option<String>,
     in_code_block: bool,
-    code_block_lang: String,
+    code_block_info: CodeInfo,
     code_block_buf: String,
     list_stack: Vec<ListKind>,
-    blockquote_depth: usize,
+    // Width of each open list item's marker, for hanging indents
+    item_indents: Vec<usize>,
+    // Kind of each open blockquote, outermost first; `None` for plain quotes
+    blockquotes: Vec<Option<BlockQuoteKind>>,
 
     // Table state
     in_table: bool,
     in_table_head: bool,
-    table_row_cells: Vec<Vec<Span<'static>>>,
-    current_cell_spans: Vec<Span<'static>>,
+    table_row_cells: Vec<Cell>,
+    current_cell_spans: Cell,
     table_col_count: usize,
-    table_header_rows: Vec<Vec<Vec<Span<'static>>>>,
-    table_body_rows: Vec<Vec<Vec<Span<'static>>>>,
+    table_alignments: Vec<Alignment>,
+    table_header_rows: Vec<Vec<Cell>>,
+    table_body_rows: Vec<Vec<Cell>>,
 
     // Inline state
     link_url: Option<String>,
+    links: Vec<Link>,
+    // Index into `links` of the link being rendered
+    open_link: Option<usize>,
+    // Index in `current_spans` where the open link's text on this line begins
+    link_start: usize,
+    // Links on the current line, as ranges of indices into `current_spans`
+    line_links: Vec<(usize, Range<usize>)>,
+    // Destinations numbered so far, for `LinkDisplay::Numbered`
+    link_references: Vec<String>,
+
+    // Footnote state
+    footnotes: Vec<Footnote>,
+    // Footnote references on the current line: footnote and span index
+    line_footnotes: Vec<(usize, usize)>,
+    // Events of each definition, replayed into the notes section at the end
+    footnote_definitions: HashMap<String, Vec<Event<'static>>>,
+    // Label and events of the definition being collected
+    footnote_definition: Option<(String, Vec<Event<'static>>)>,
+
+    // Front matter state
+    front_matter: Option<FrontMatter>,
+}
+
+/// What a span of text on the current line belongs to.
+#[derive(Clone, Copy, PartialEq)]
+enum Target {
+    Link(usize),
+    Footnote(usize),
 }
 
+/// The spans of a table cell, each tagged with what it belongs to so links
+/// and footnote references can be located once the table is laid out.
+type Cell = Vec<(Span<'static>, Option<Target>)>;
+
 #[derive(Clone)]
 enum ListKind {
     Unordered(usize), // nesting depth (0-based)
@@ -42,43 +173,200 @@ const BULLETS: &[&str] = &["• ", "◦ ", "▪ "];
 impl Renderer {
     pub fn new() -> Self {
         Self {
+            theme: Theme::default(),
+            code_options: CodeOptions::default(),
+            #[cfg(feature = "syntect")]
+            syntect: None,
+            guess_languages: false,
+            width: None,
+            link_display: LinkDisplay::default(),
+            front_matter_display: FrontMatterDisplay::default(),
+            math: true,
             lines: Vec::new(),
+            code_blocks: Vec::new(),
+            outline: Vec::new(),
+            anchors: HashMap::new(),
+            slugs: HashMap::new(),
             current_spans: Vec::new(),
+            prefix_len: 0,
+            trim_next_text: false,
             style_stack: vec![Style::default()],
+            heading: None,
+            heading_id: None,
             in_code_block: false,
-            code_block_lang: String::new(),
+            code_block_info: CodeInfo::default(),
             code_block_buf: String::new(),
             list_stack: Vec::new(),
-            blockquote_depth: 0,
+            item_indents: Vec::new(),
+            blockquotes: Vec::new(),
             in_table: false,
             in_table_head: false,
             table_row_cells: Vec::new(),
             current_cell_spans: Vec::new(),
             table_col_count: 0,
+            table_alignments: Vec::new(),
             table_header_rows: Vec::new(),
             table_body_rows: Vec::new(),
             link_url: None,
+            links: Vec::new(),
+            open_link: None,
+            link_start: 0,
+            line_links: Vec::new(),
+            link_references: Vec::new(),
+            footnotes: Vec::new(),
+            line_footnotes: Vec::new(),
+            footnote_definitions: HashMap::new(),
+            footnote_definition: None,
+            front_matter: None,
         }
     }
 
+    /// Use `theme` for all styling instead of [`Theme::default()`].
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Highlight code blocks with `highlighter`, e.g. a `SyntectHighlighter`
+    /// with a custom theme or a `TreeSitterHighlighter`.
+    pub fn highlighter(mut self, highlighter: impl Highlighter + 'static) -> Self {
+        self.code_options.highlighter = Arc::new(highlighter);
+        #[cfg(feature = "syntect")]
+        {
+            self.syntect = None;
+        }
+        self
+    }
+
+    /// Highlight code blocks with syntect and `theme` instead of
+    /// [`SyntaxTheme::default()`]. Shorthand for a [`SyntectHighlighter`].
+    #[cfg(feature = "syntect")]
+    pub fn syntax_theme(self, theme: SyntaxTheme) -> Self {
+        self.with_syntect(|h| h.theme(theme))
+    }
+
+    /// Highlight code blocks with syntect, looking up languages in
+    /// `syntaxes` instead of the defaults. Shorthand for a
+    /// [`SyntectHighlighter`].
+    #[cfg(feature = "syntect")]
+    pub fn syntaxes(self, syntaxes: Syntaxes) -> Self {
+        self.with_syntect(|h| h.syntaxes(syntaxes))
+    }
+
+    #[cfg(feature = "syntect")]
+    fn with_syntect(mut self, f: impl FnOnce(SyntectHighlighter) -> SyntectHighlighter) -> Self {
+        let highlighter = f(self.syntect.take().unwrap_or_default());
+        self.code_options.highlighter = Arc::new(highlighter.clone());
+        self.syntect = Some(highlighter);
+        self
+    }
+
+    /// Choose where code blocks take their background color from.
+    pub fn code_background(mut self, background: CodeBackground) -> Self {
+        self.code_options.background = background;
+        self
+    }
+
+    /// Quantize every emitted color to `depth`. See [`ColorDepth::detect`].
+    pub fn color_depth(mut self, depth: ColorDepth) -> Self {
+        self.code_options.color_depth = depth;
+        self
+    }
+
+    /// Show a line-number gutter on every code block. Without this, only
+    /// blocks whose info string asks for it (`linenos`, `startline=120`, …)
+    /// are numbered.
+    pub fn line_numbers(mut self, enabled: bool) -> Self {
+        self.code_options.line_numbers = enabled;
+        self
+    }
+
+    /// Guess the language of code blocks whose fence names none (off by
+    /// default). See [`guess_language`](crate::guess_language).
+    pub fn guess_languages(mut self, enabled: bool) -> Self {
+        self.guess_languages = enabled;
+        self
+    }
+
+    /// Wrap prose to `width` columns. Continuation lines repeat blockquote
+    /// bars and hang under the text of list items. Code blocks are not
+    /// wrapped. Without a width, each paragraph line is one output line.
+    pub fn width(mut self, width: u16) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Choose how link destinations are shown. Defaults to
+    /// [`LinkDisplay::Suffix`].
+    pub fn link_display(mut self, display: LinkDisplay) -> Self {
+        self.link_display = display;
+        self
+    }
+
+    /// Choose how front matter is shown. It is parsed into
+    /// [`RenderOutput::front_matter`] whatever the mode; defaults to
+    /// [`FrontMatterDisplay::Hidden`].
+    pub fn front_matter_display(mut self, display: FrontMatterDisplay) -> Self {
+        self.front_matter_display = display;
+        self
+    }
+
+    /// Render `$…$` and `$$…$$` as math (on by default). Turn it off for
+    /// documents that use dollar signs for prices, where `$5-$10` would
+    /// otherwise be read as math.
+    pub fn math(mut self, enabled: bool) -> Self {
+        self.math = enabled;
+        self
+    }
+
     /// Render markdown input into styled ratatui lines.
-    pub fn render(mut self, input: &str) -> Vec<Line<'static>> {
+    pub fn render(self, input: &str) -> Vec<Line<'static>> {
+        self.render_output(input).lines
+    }
+
+    /// Render `input` at `width` with this renderer's settings, starting
+    /// from fresh state and leaving `self` as it was.
+    pub(crate) fn render_output_at(&self, input: &str, width: u16) -> RenderOutput {
+        Self {
+            theme: self.theme.clone(),
+            code_options: self.code_options.clone(),
+            guess_languages: self.guess_languages,
+            width: Some(width),
+            link_display: self.link_display,
+            front_matter_display: self.front_matter_display,
+            math: self.math,
+            ..Self::new()
+        }
+        .render_output(input)
+    }
+
+    /// Render markdown input into styled lines plus document metadata.
+    pub fn render_output(mut self, input: &str) -> RenderOutput {
         if input.trim().is_empty() {
-            return vec![Line::from("")];
+            return RenderOutput {
+                lines: vec![Line::from("")],
+                ..Default::default()
+            };
         }
 
-        let mut options = Options::empty();
-        options.insert(Options::ENABLE_STRIKETHROUGH);
-        options.insert(Options::ENABLE_TASKLISTS);
-        options.insert(Options::ENABLE_TABLES);
-        options.insert(Options::ENABLE_SMART_PUNCTUATION);
+        // Front matter is split off first, so only the top of the document
+        // is ever read as metadata
+        let body = match front_matter::split(input) {
+            Some((format, source, body)) => {
+                self.push_front_matter(FrontMatter::parse(source, format));
+                body
+            }
+            None => input,
+        };
 
-        let events: Vec<Event<'_>> = Parser::new_ext(input, options).collect();
+        let events: Vec<Event<'_>> = Parser::new_ext(body, self.markdown_options()).collect();
         for event in events {
             self.process_event(event);
         }
 
         self.flush_spans();
+        self.push_footnotes();
+        self.push_link_references();
 
         // Remove trailing blank lines
         while self.lines.last().is_some_and(|l| line_is_blank(l)) {
@@ -89,15 +377,39 @@ impl Renderer {
             self.lines.push(Line::from(""));
         }
 
-        self.lines
+        self.code_options.color_depth.apply(&mut self.lines);
+        RenderOutput {
+            lines: self.lines,
+            code_blocks: self.code_blocks,
+            outline: self.outline,
+            anchors: self.anchors,
+            links: self.links,
+            footnotes: self.footnotes,
+            front_matter: self.front_matter,
+        }
     }
 
     fn process_event(&mut self, event: Event<'_>) {
+        if let Some((label, events)) = &mut self.footnote_definition {
+            if event == Event::End(TagEnd::FootnoteDefinition) {
+                let label = std::mem::take(label);
+                let events = std::mem::take(events);
+                self.footnote_definitions.entry(label).or_insert(events);
+                self.footnote_definition = None;
+            } else {
+                events.push(event.into_static());
+            }
+            return;
+        }
+
         match event {
             Event::Start(tag) => self.start_tag(tag),
             Event::End(tag_end) => self.end_tag(tag_end),
             Event::Text(text) => self.handle_text(&text),
             Event::Code(code) => self.handle_inline_code(&code),
+            Event::FootnoteReference(label) => self.handle_footnote_reference(&label),
+            Event::InlineMath(tex) => self.handle_inline_math(&tex),
+            Event::DisplayMath(tex) => self.handle_display_math(&tex),
             Event::SoftBreak | Event::HardBreak => self.handle_break(),
             Event::TaskListMarker(checked) => self.handle_task_marker(checked),
             Event::Rule => self.handle_rule(),
@@ -109,18 +421,22 @@ impl Renderer {
 
     fn start_tag(&mut self, tag: Tag<'_>) {
         match tag {
-            Tag::Heading { level, .. } => self.start_heading(level),
+            Tag::Heading { level, id, .. } => {
+                self.start_heading(level as usize, id.map(|id| id.to_string()))
+            }
             Tag::Paragraph => self.start_paragraph(),
             Tag::Strong => self.push_modifier(Modifier::BOLD),
             Tag::Emphasis => self.push_modifier(Modifier::ITALIC),
             Tag::Strikethrough => self.push_modifier(Modifier::CROSSED_OUT),
             Tag::CodeBlock(kind) => self.start_code_block(kind),
-            Tag::BlockQuote(_) => self.start_blockquote(),
+            Tag::BlockQuote(kind) => self.start_blockquote(kind),
             Tag::List(first_item) => self.start_list(first_item),
             Tag::Item => self.start_item(),
-            Tag::Link { dest_url, .. } => self.start_link(dest_url.to_string()),
+            Tag::Link {
+                dest_url, title, ..
+            } => self.start_link(dest_url.to_string(), title.to_string()),
             Tag::Image { dest_url, .. } => self.start_image(dest_url.to_string()),
-            Tag::Table(_) => self.start_table(),
+            Tag::Table(alignments) => self.start_table(alignments),
             Tag::TableHead => self.in_table_head = true,
             Tag::TableRow => {
                 self.table_row_cells.clear();
@@ -128,47 +444,46 @@ impl Renderer {
             Tag::TableCell => {
                 self.current_cell_spans.clear();
             }
+            Tag::FootnoteDefinition(label) => {
+                self.footnote_definition = Some((label.to_string(), Vec::new()));
+            }
             _ => {}
         }
     }
 
     fn start_paragraph(&mut self) {
-        // Add blockquote prefix at the start of paragraphs inside blockquotes
-        if self.blockquote_depth > 0 {
-            let mut spans = Vec::new();
-            self.add_blockquote_prefix(&mut spans);
-            self.current_spans = spans;
+        // A paragraph that doesn't open a list item starts with the container
+        // prefix: blockquote bars and the indent of the enclosing item.
+        if self.current_spans.is_empty() {
+            self.start_continuation();
         }
     }
 
-    fn start_heading(&mut self, level: pulldown_cmark::HeadingLevel) {
-        let style = match level {
-            pulldown_cmark::HeadingLevel::H1 => {
-                Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
-            }
-            pulldown_cmark::HeadingLevel::H2 => {
-                Style::default()
-                    .fg(Color::Green)
-                    .add_modifier(Modifier::BOLD)
-            }
-            _ => Style::default()
-                .fg(Color::Yellow)
-                .add_modifier(Modifier::BOLD),
-        };
-        self.style_stack.push(style);
+    fn start_heading(&mut self, level: usize, id: Option<String>) {
+        self.style_stack.push(self.theme.heading(level));
+        self.heading = Some((level, String::new()));
+        self.heading_id = id;
     }
 
     fn start_code_block(&mut self, kind: CodeBlockKind<'_>) {
         self.in_code_block = true;
         self.code_block_buf.clear();
-        self.code_block_lang = match kind {
-            CodeBlockKind::Fenced(lang) => lang.to_string(),
-            CodeBlockKind::Indented => String::new(),
+        self.code_block_info = match kind {
+            CodeBlockKind::Fenced(info) => CodeInfo::parse(&info),
+            CodeBlockKind::Indented => CodeInfo::default(),
         };
     }
 
-    fn start_blockquote(&mut self) {
-        self.blockquote_depth += 1;
+    fn start_blockquote(&mut self, kind: Option<BlockQuoteKind>) {
+        self.flush_spans();
+        self.blockquotes.push(kind);
+        if let Some(kind) = kind {
+            // Alerts open with an icon and title on the bar
+            let (icon, title) = alert_label(kind);
+            let mut spans = self.continuation_prefix();
+            spans.push(Span::styled(format!("{} {}", icon, title), self.alert_style(kind)));
+            self.lines.push(Line::from(spans));
+        }
     }
 
     fn start_list(&mut self, first_item: Option<u64>) {
@@ -189,16 +504,10 @@ impl Renderer {
         let marker_span = match self.list_stack.last() {
             Some(ListKind::Unordered(depth)) => {
                 let bullet = BULLETS[*depth % BULLETS.len()];
-                Span::styled(
-                    format!("{}{}", indent, bullet),
-                    Style::default().fg(Color::Cyan),
-                )
+                Span::styled(format!("{}{}", indent, bullet), self.theme.list_marker)
             }
             Some(ListKind::Ordered(n)) => {
-                let span = Span::styled(
-                    format!("{}{}. ", indent, n),
-                    Style::default().fg(Color::Cyan),
-                );
+                let span = Span::styled(format!("{}{}. ", indent, n), self.theme.list_marker);
                 // Increment counter for next item
                 if let Some(ListKind::Ordered(num)) = self.list_stack.last_mut() {
                     *num += 1;
@@ -210,28 +519,34 @@ impl Renderer {
 
         let mut spans = Vec::new();
         self.add_blockquote_prefix(&mut spans);
+        self.item_indents.push(marker_span.width());
         spans.push(marker_span);
+        self.prefix_len = spans.len();
         self.current_spans = spans;
     }
 
-    fn start_link(&mut self, url: String) {
+    fn start_link(&mut self, url: String, title: String) {
+        self.open_link = Some(self.links.len());
+        self.link_start = self.current_spans.len();
+        self.links.push(Link {
+            url: url.clone(),
+            title,
+            ..Default::default()
+        });
         self.link_url = Some(url);
         let base = *self.style_stack.last().unwrap_or(&Style::default());
-        self.style_stack
-            .push(base.fg(Color::Blue).add_modifier(Modifier::UNDERLINED));
+        self.style_stack.push(base.patch(self.theme.link));
     }
 
     fn start_image(&mut self, _url: String) {
         // We'll handle the alt text in handle_text when we see it
-        self.current_spans.push(Span::styled(
-            "[img: ",
-            Style::default().fg(Color::DarkGray),
-        ));
+        self.push_inline(Span::styled("[img: ", self.theme.image));
     }
 
-    fn start_table(&mut self) {
+    fn start_table(&mut self, alignments: Vec<Alignment>) {
         self.in_table = true;
         self.table_col_count = 0;
+        self.table_alignments = alignments;
         self.table_header_rows.clear();
         self.table_body_rows.clear();
     }
@@ -271,7 +586,26 @@ impl Renderer {
 
     fn end_heading(&mut self) {
         self.style_stack.pop();
+        let line = self.lines.len();
         self.flush_spans();
+        if let Some((level, text)) = self.heading.take() {
+            let slug = match self.heading_id.take() {
+                Some(id) => {
+                    // Keep later generated slugs from colliding with the explicit id
+                    self.slugs.entry(id.clone()).or_insert(0);
+                    id
+                }
+                None => outline::unique_slug(&text, &mut self.slugs),
+            };
+            // An anchor already taken keeps pointing at its heading, so links stay put
+            self.anchors.entry(slug.clone()).or_insert(line);
+            self.outline.push(Heading {
+                level,
+                text,
+                slug,
+                line,
+            });
+        }
         self.push_blank_line();
     }
 
@@ -283,35 +617,123 @@ impl Renderer {
     fn end_code_block(&mut self) {
         self.in_code_block = false;
         let code = std::mem::take(&mut self.code_block_buf);
-        let lang = std::mem::take(&mut self.code_block_lang);
+        let info = std::mem::take(&mut self.code_block_info);
+        let lang = info.language.as_str();
 
         // Trim trailing newline from code
         let code = code.trim_end_matches('\n');
 
-        let highlighted = if lang.is_empty() {
-            syntax::highlight_code(code, "")
+        let inferred_language = if lang.is_empty() && self.guess_languages {
+            syntax::guess_language(code, self.code_options.highlighter.as_ref())
         } else {
-            syntax::highlight_code(code, &lang)
+            None
         };
+        let highlight_lang = inferred_language.as_deref().unwrap_or(lang);
+        let highlighted = syntax::highlight_code_block(
+            code,
+            highlight_lang,
+            &info,
+            &self.theme,
+            &self.code_options,
+        );
+
+        let block_line = self.lines.len();
 
-        // Add language label line if specified
-        if !lang.is_empty() {
-            self.lines.push(Line::from(Span::styled(
-                format!("    {}", lang),
-                Style::default().fg(Color::DarkGray).bg(CODE_BG),
-            )));
+        // Add a caption line: the title attribute if given, else the language
+        let caption = info.title().unwrap_or(lang);
+        if !caption.is_empty() {
+            let mut label_style = self.theme.code_block_label;
+            if let Some(bg) = self.code_options.block_background(&self.theme) {
+                label_style = label_style.bg(bg);
+            }
+            self.lines
+                .push(Line::from(Span::styled(format!("    {}", caption), label_style)));
         }
 
         for line in highlighted {
             self.lines.push(line);
         }
 
+        // Inside a blockquote or list item, keep the bars and indent going
+        let prefix = self.continuation_prefix();
+        if !prefix.is_empty() {
+            for line in &mut self.lines[block_line..] {
+                line.spans.splice(0..0, prefix.iter().cloned());
+            }
+        }
+
+        self.code_blocks.push(CodeBlockInfo {
+            line: block_line,
+            info,
+            inferred_language,
+        });
+
         self.push_blank_line();
     }
 
+    fn push_front_matter(&mut self, matter: FrontMatter) {
+        match self.front_matter_display {
+            FrontMatterDisplay::Hidden => {}
+            FrontMatterDisplay::Card => self.push_front_matter_card(&matter),
+            FrontMatterDisplay::Table => self.push_front_matter_table(&matter),
+        }
+        self.front_matter = Some(matter);
+    }
+
+    /// The title as a heading, then author and date, then tags. Values of
+    /// several lines are joined into one.
+    fn push_front_matter_card(&mut self, matter: &FrontMatter) {
+        let value = |keys: &[&str]| keys.iter().find_map(|key| matter.get(key));
+        let text = |value: &FrontMatterValue| value.to_string().replace('\n', " ");
+        let mut rows = Vec::new();
+        if let Some(title) = value(&["title"]) {
+            rows.push(Span::styled(text(title), self.theme.heading(1)));
+        }
+        let byline: Vec<String> = [value(&["author", "authors", "author.name"]), value(&["date"])]
+            .into_iter()
+            .flatten()
+            .map(text)
+            .collect();
+        if !byline.is_empty() {
+            rows.push(Span::styled(byline.join(" · "), self.theme.front_matter));
+        }
+        if let Some(tags) = value(&["tags", "keywords", "categories"]) {
+            let tags: Vec<String> = tags.items().iter().map(|tag| format!("#{}", tag)).collect();
+            rows.push(Span::styled(tags.join(" "), self.theme.front_matter));
+        }
+
+        for row in rows {
+            self.current_spans.push(row);
+            self.flush_spans();
+        }
+        self.push_blank_line();
+    }
+
+    /// Every entry as a row of a key/value table, with a row per line of
+    /// values that span several.
+    fn push_front_matter_table(&mut self, matter: &FrontMatter) {
+        if matter.entries.is_empty() {
+            return;
+        }
+        let cell = |text: &str| vec![(Span::raw(text.to_string()), None)];
+        self.table_header_rows = vec![vec![cell("Key"), cell("Value")]];
+        self.table_body_rows = matter
+            .entries
+            .iter()
+            .flat_map(|(key, value)| {
+                let value = value.to_string();
+                let keys = std::iter::once(key.as_str()).chain(std::iter::repeat(""));
+                let lines = value.split('\n');
+                keys.zip(lines).map(|(k, v)| vec![cell(k), cell(v)]).collect::<Vec<_>>()
+            })
+            .collect();
+        self.table_alignments = Vec::new();
+        self.end_table();
+    }
+
     fn end_blockquote(&mut self) {
-        self.blockquote_depth = self.blockquote_depth.saturating_sub(1);
-        if self.blockquote_depth == 0 {
+        self.blockquotes.pop();
+        if self.blockquotes.is_empty() {
             self.push_blank_line();
         }
     }
@@ -325,23 +747,106 @@ impl Renderer {
 
     fn end_item(&mut self) {
         self.flush_spans();
+        self.item_indents.pop();
     }
 
     fn end_link(&mut self) {
         self.style_stack.pop();
-        if let Some(url) = self.link_url.take() {
-            self.current_spans.push(Span::styled(
-                format!(" ({})", url),
-                Style::default().fg(Color::DarkGray),
-            ));
+        // The position covers the link text, not the destination after it
+        if let Some(link) = self.open_link.take()
+            && !self.in_table
+        {
+            self.line_links
+                .push((link, self.link_start..self.current_spans.len()));
+        }
+        // Fragment links jump within the document, so their `#id` adds nothing
+        if let Some(url) = self.link_url.take()
+            && !url.starts_with('#')
+        {
+            match self.link_display {
+                LinkDisplay::Suffix => {
+                    self.push_inline(Span::styled(format!(" ({})", url), self.theme.link_url));
+                }
+                LinkDisplay::Numbered => {
+                    let number = match self.link_references.iter().position(|u| *u == url) {
+                        Some(index) => index + 1,
+                        None => {
+                            self.link_references.push(url);
+                            self.link_references.len()
+                        }
+                    };
+                    self.push_inline(Span::styled(format!("[{}]", number), self.theme.link_url));
+                }
+                LinkDisplay::Hyperlink | LinkDisplay::Hidden => {}
+            }
+        }
+    }
+
+    /// Render the definitions of referenced footnotes as a numbered list
+    /// under a "Footnotes" heading. Notes referenced only from other notes
+    /// are numbered while this runs and are listed after them.
+    fn push_footnotes(&mut self) {
+        let mut heading = false;
+        let mut index = 0;
+        while index < self.footnotes.len() {
+            let label = &self.footnotes[index].label;
+            if let Some(events) = self.footnote_definitions.remove(label) {
+                if !heading {
+                    self.push_blank_line();
+                    self.lines.push(Line::from(Span::styled("Footnotes", self.theme.heading(2))));
+                    self.push_blank_line();
+                    heading = true;
+                }
+
+                self.footnotes[index].line = self.lines.len();
+                let marker = Span::styled(format!("{}. ", index + 1), self.theme.footnote);
+                self.item_indents.push(marker.width());
+                if matches!(events.first(), Some(Event::Start(Tag::Paragraph))) {
+                    self.current_spans = vec![marker];
+                    self.prefix_len = 1;
+                } else {
+                    // A list, code block, quote or table starts below the number
+                    let number = Span::styled(format!("{}.", index + 1), self.theme.footnote);
+                    self.lines.push(Line::from(number));
+                }
+                for event in events {
+                    self.process_event(event);
+                }
+                self.flush_spans();
+                self.item_indents.pop();
+            }
+            index += 1;
+        }
+    }
+
+    /// List the destinations numbered by [`LinkDisplay::Numbered`] under a
+    /// "Links" heading, each recorded as a link to itself.
+    fn push_link_references(&mut self) {
+        if self.link_references.is_empty() {
+            return;
+        }
+        self.push_blank_line();
+        self.lines.push(Line::from(Span::styled("Links", self.theme.heading(2))));
+        self.push_blank_line();
+        for (i, url) in std::mem::take(&mut self.link_references).into_iter().enumerate() {
+            let marker = Span::styled(format!("[{}] ", i + 1), self.theme.list_marker);
+            self.item_indents.push(marker.width());
+            self.current_spans = vec![marker];
+            self.prefix_len = 1;
+            self.line_links.push((self.links.len(), 1..2));
+            self.links.push(Link {
+                url: url.clone(),
+                text: url.clone(),
+                ..Default::default()
+            });
+            self.current_spans.push(Span::styled(url, self.theme.link_url));
+            self.flush_spans();
+            self.item_indents.pop();
         }
     }
 
     fn end_image(&mut self) {
-        self.current_spans.push(Span::styled(
-            "]",
-            Style::default().fg(Color::DarkGray),
-        ));
+        self.push_inline(Span::styled("]", self.theme.image));
     }
 
     fn end_table(&mut self) {
@@ -352,8 +857,7 @@ impl Renderer {
         let body_rows = std::mem::take(&mut self.table_body_rows);
 
         // Compute column widths from all rows
-        let all_rows: Vec<&Vec<Vec<Span<'static>>>> =
-            header_rows.iter().chain(body_rows.iter()).collect();
+        let all_rows: Vec<&Vec<Cell>> = header_rows.iter().chain(body_rows.iter()).collect();
 
         if all_rows.is_empty() {
             return;
@@ -367,48 +871,99 @@ impl Renderer {
         // Calculate max content width per column (minimum 3)
         let mut col_widths = vec![3usize; col_count];
         for row in &all_rows {
-            for (i, cell_spans) in row.iter().enumerate() {
-                let content_len: usize = cell_spans.iter().map(|s| s.content.len()).sum();
+            for (i, cell) in row.iter().enumerate() {
+                let content_len: usize = cell.iter().map(|(span, _)| span.width()).sum();
                 col_widths[i] = col_widths[i].max(content_len + 2);
             }
         }
 
-        let border_style = Style::default().fg(Color::DarkGray);
+        // Inside a list or blockquote, lay the table out beside the indent
+        // and bars that start each of its lines
+        let prefix = self.continuation_prefix();
+        let prefix_width: usize = prefix.iter().map(Span::width).sum();
+
+        // Shrink columns to fit the width, or fall back to one record per row
+        let width = self.width.map(|w| usize::from(w).saturating_sub(prefix_width));
+        match width.map(|width| (width, fit_column_widths(&col_widths, width))) {
+            Some((_, Some(fitted))) => col_widths = fitted,
+            Some((width, None)) => {
+                self.push_stacked_table(&prefix, &header_rows, &body_rows, width.max(1));
+                self.table_col_count = 0;
+                self.push_blank_line();
+                return;
+            }
+            None => {}
+        }
+
+        let border_style = self.theme.table_border;
+        let alignments = std::mem::take(&mut self.table_alignments);
+        let border = |left, mid, right| {
+            let border = build_table_border(&col_widths, left, mid, right);
+            vec![(Span::styled(border, border_style), None)]
+        };
 
         // Top border
-        self.lines.push(Line::from(Span::styled(
-            build_table_border(&col_widths, '┌', '┬', '┐'),
-            border_style,
-        )));
+        self.push_tagged_line(&prefix, border('┌', '┬', '┐'));
 
         // Header rows
         for row in &header_rows {
-            self.lines
-                .push(build_table_row_line(row, &col_widths, true));
+            for line in build_table_row_lines(row, &col_widths, &alignments, true, &self.theme) {
+                self.push_tagged_line(&prefix, line);
+            }
         }
 
         // Middle border
-        self.lines.push(Line::from(Span::styled(
-            build_table_border(&col_widths, '├', '┼', '┤'),
-            border_style,
-        )));
+        self.push_tagged_line(&prefix, border('├', '┼', '┤'));
 
         // Body rows
         for row in &body_rows {
-            self.lines
-                .push(build_table_row_line(row, &col_widths, false));
+            for line in build_table_row_lines(row, &col_widths, &alignments, false, &self.theme) {
+                self.push_tagged_line(&prefix, line);
+            }
         }
 
         // Bottom border
-        self.lines.push(Line::from(Span::styled(
-            build_table_border(&col_widths, '└', '┴', '┘'),
-            border_style,
-        )));
+        self.push_tagged_line(&prefix, border('└', '┴', '┘'));
 
         self.table_col_count = 0;
         self.push_blank_line();
     }
 
+    /// Render each body row as a block of `Header: value` lines, for tables
+    /// too wide to draw as a grid even with minimal columns.
+    fn push_stacked_table(
+        &mut self,
+        prefix: &[Span<'static>],
+        header_rows: &[Vec<Cell>],
+        body_rows: &[Vec<Cell>],
+        width: usize,
+    ) {
+        let headers = header_rows.first().map(Vec::as_slice).unwrap_or_default();
+        let separator = Span::styled("─".repeat(width.min(40)), self.theme.table_border);
+
+        for (r, row) in body_rows.iter().enumerate() {
+            if r > 0 {
+                self.push_tagged_line(prefix, vec![(separator.clone(), None)]);
+            }
+            for (i, cell) in row.iter().enumerate() {
+                let mut spans = headers.get(i).cloned().unwrap_or_default();
+                if !spans.is_empty() {
+                    spans.push((Span::raw(": "), None));
+                    header_styled(&mut spans, &self.theme);
+                }
+                spans.extend(cell.iter().cloned());
+
+                let wrapped = wrap::wrap_tagged(&spans, width, width.saturating_sub(2));
+                for (k, mut line_spans) in wrapped.into_iter().enumerate() {
+                    if k > 0 {
+                        line_spans.insert(0, (Span::raw("  "), None));
+                    }
+                    self.push_tagged_line(prefix, line_spans);
+                }
+            }
+        }
+    }
+
     // ── Content handlers ──
 
     fn handle_text(&mut self, text: &str) {
@@ -417,25 +972,35 @@ impl Renderer {
             return;
         }
 
+        if let Some((_, heading_text)) = &mut self.heading {
+            heading_text.push_str(text);
+        }
+        if let Some(link) = self.open_link {
+            self.links[link].text.push_str(text);
+        }
+
+        // Header cells get the header style at layout time, under these
         if self.in_table {
-            let style = if self.in_table_head {
-                Style::default()
-                    .fg(Color::Cyan)
-                    .add_modifier(Modifier::BOLD)
-            } else {
-                *self.style_stack.last().unwrap_or(&Style::default())
-            };
-            self.current_cell_spans
-                .push(Span::styled(text.to_string(), style));
+            let style = self.current_style();
+            self.push_inline(Span::styled(text.to_string(), style));
             return;
         }
 
+        // Drop the space between display math and the text resuming after it
+        let text = if std::mem::take(&mut self.trim_next_text) {
+            text.trim_start()
+        } else {
+            text
+        };
+        if text.is_empty() {
+            return;
+        }
         let style = *self.style_stack.last().unwrap_or(&Style::default());
 
-        // For blockquotes, we need to handle newlines within the text
-        if self.blockquote_depth > 0 {
+        // Text in a plain blockquote gets the quote style; alerts keep theirs
+        if let Some(None) = self.blockquotes.last() {
             let bq_style = if style == Style::default() {
-                Style::default().add_modifier(Modifier::ITALIC)
+                self.theme.blockquote_text
             } else {
                 style
             };
@@ -448,28 +1013,93 @@ impl Renderer {
     }
 
     fn handle_inline_code(&mut self, code: &str) {
+        if let Some((_, heading_text)) = &mut self.heading {
+            heading_text.push_str(code);
+        }
+        if let Some(link) = self.open_link {
+            self.links[link].text.push_str(code);
+        }
+        self.push_inline(Span::styled(code.to_string(), self.theme.inline_code));
+    }
+
+    fn handle_inline_math(&mut self, tex: &str) {
+        let span = match math::inline(tex) {
+            Some(text) => Span::styled(text, self.theme.math),
+            None => Span::styled(format!("${}$", tex), self.theme.math_raw),
+        };
+        if let Some((_, heading_text)) = &mut self.heading {
+            heading_text.push_str(&span.content);
+        }
+        if let Some(link) = self.open_link {
+            self.links[link].text.push_str(&span.content);
+        }
+        self.push_inline(span);
+    }
+
+    /// Set `$$…$$` on lines of its own, indented like code, with fractions
+    /// stacked. Inside a table cell it is drawn inline instead.
+    fn handle_display_math(&mut self, tex: &str) {
         if self.in_table {
-            self.current_cell_spans.push(Span::styled(
-                code.to_string(),
-                Style::default().fg(Color::Magenta),
-            ));
+            let span = match math::inline(tex) {
+                Some(text) => Span::styled(text, self.theme.math),
+                None => Span::styled(format!("$${}$$", tex), self.theme.math_raw),
+            };
+            self.push_inline(span);
             return;
         }
-        self.current_spans.push(Span::styled(
-            code.to_string(),
-            Style::default().fg(Color::Magenta),
-        ));
+
+        if self.current_spans.len() > self.prefix_len
+            && let Some(last) = self.current_spans.last_mut()
+        {
+            let trimmed = last.content.trim_end().to_string();
+            last.content = trimmed.into();
+        }
+        self.flush_spans();
+        let (rows, style) = match math::display(tex) {
+            Some(rows) => (rows, self.theme.math),
+            None => (tex.trim().lines().map(str::to_string).collect(), self.theme.math_raw),
+        };
+        for row in rows {
+            let mut spans = self.continuation_prefix();
+            spans.push(Span::styled(format!("    {}", row), style));
+            self.lines.push(Line::from(spans));
+        }
+        self.start_continuation();
+        self.trim_next_text = true;
+    }
+
+    /// Draw a superscript marker numbered by first reference.
+    fn handle_footnote_reference(&mut self, label: &str) {
+        let index = match self.footnotes.iter().position(|note| note.label == label) {
+            Some(index) => index,
+            None => {
+                self.footnotes.push(Footnote {
+                    label: label.to_string(),
+                    ..Default::default()
+                });
+                self.footnotes.len() - 1
+            }
+        };
+        let marker = Span::styled(footnotes::superscript(index + 1), self.theme.footnote);
+        if self.in_table {
+            self.current_cell_spans
+                .push((marker, Some(Target::Footnote(index))));
+            return;
+        }
+        self.line_footnotes.push((index, self.current_spans.len()));
+        self.push_inline(marker);
     }
 
     fn handle_break(&mut self) {
         self.flush_spans();
+        self.start_continuation();
     }
 
     fn handle_task_marker(&mut self, checked: bool) {
-        let (marker, color) = if checked {
-            ("  ☑ ", Color::Green)
+        let (marker, style) = if checked {
+            ("  ☑ ", self.theme.task_checked)
         } else {
-            ("  ☐ ", Color::Yellow)
+            ("  ☐ ", self.theme.task_unchecked)
         };
 
         // Replace the last list marker spans (the bullet) with the task marker
@@ -479,38 +1109,169 @@ impl Renderer {
         self.add_blockquote_prefix(&mut spans);
         let indent_level = self.list_stack.len().saturating_sub(1);
         let indent = "  ".repeat(indent_level);
-        spans.push(Span::styled(
-            format!("{}{}", indent, marker),
-            Style::default().fg(color),
-        ));
+        let marker_span = Span::styled(format!("{}{}", indent, marker), style);
+        if let Some(item_indent) = self.item_indents.last_mut() {
+            *item_indent = marker_span.width();
+        }
+        spans.push(marker_span);
+        self.prefix_len = spans.len();
         self.current_spans = spans;
     }
 
     fn handle_rule(&mut self) {
         self.flush_spans();
+        let width = self.width.map_or(40, |w| usize::from(w).min(40));
         self.lines.push(Line::from(Span::styled(
-            "─".repeat(40),
-            Style::default().fg(Color::DarkGray),
+            "─".repeat(width),
+            self.theme.rule,
         )));
         self.push_blank_line();
     }
 
     // ── Helpers ──
 
+    /// Parser options. Metadata blocks are left out: front matter is split
+    /// off before parsing.
+    fn markdown_options(&self) -> Options {
+        let mut options = Options::empty();
+        options.insert(Options::ENABLE_STRIKETHROUGH);
+        options.insert(Options::ENABLE_TASKLISTS);
+        options.insert(Options::ENABLE_TABLES);
+        options.insert(Options::ENABLE_SMART_PUNCTUATION);
+        options.insert(Options::ENABLE_FOOTNOTES);
+        options.insert(Options::ENABLE_GFM);
+        options.insert(Options::ENABLE_HEADING_ATTRIBUTES);
+        if self.math {
+            options.insert(Options::ENABLE_MATH);
+        }
+        options
+    }
+
     fn current_style(&self) -> Style {
         *self.style_stack.last().unwrap_or(&Style::default())
     }
 
+    /// Append an inline span to the current table cell or text line.
+    fn push_inline(&mut self, span: Span<'static>) {
+        self.trim_next_text = false;
+        if self.in_table {
+            let target = self.open_link.map(Target::Link);
+            self.current_cell_spans.push((span, target));
+        } else {
+            self.current_spans.push(span);
+        }
+    }
+
     fn push_modifier(&mut self, modifier: Modifier) {
         let base = self.current_style();
         self.style_stack.push(base.add_modifier(modifier));
     }
 
     fn flush_spans(&mut self) {
-        if !self.current_spans.is_empty() {
-            let spans: Vec<Span<'static>> = self.current_spans.drain(..).collect();
-            self.lines.push(Line::from(spans));
+        self.trim_next_text = false;
+        let prefix_len = std::mem::take(&mut self.prefix_len).min(self.current_spans.len());
+        let mut spans: Vec<Span<'static>> = self.current_spans.drain(..).collect();
+        let mut line_links = std::mem::take(&mut self.line_links);
+        let line_footnotes = std::mem::take(&mut self.line_footnotes);
+        if let Some(link) = self.open_link
+            && !self.in_table
+        {
+            // A link broken by a soft break continues on the next line
+            line_links.push((link, self.link_start..spans.len()));
+        }
+        // A prefix with no text after it is dropped rather than emitted.
+        if spans.len() == prefix_len {
+            return;
+        }
+
+        // Tag each content span with the link or footnote reference it belongs to
+        let content: Vec<(Span<'static>, Option<Target>)> = spans
+            .split_off(prefix_len)
+            .into_iter()
+            .enumerate()
+            .map(|(i, span)| {
+                let index = prefix_len + i;
+                let link = line_links.iter().find(|(_, r)| r.contains(&index));
+                let footnote = line_footnotes.iter().find(|(_, i)| *i == index);
+                let target = link
+                    .map(|(link, _)| Target::Link(*link))
+                    .or(footnote.map(|(note, _)| Target::Footnote(*note)));
+                (span, target)
+            })
+            .collect();
+        let first_prefix = spans;
+        let rest_prefix = self.continuation_prefix();
+
+        let wrapped = match self.width {
+            None => vec![content],
+            Some(width) => {
+                let prefix_width =
+                    |prefix: &[Span<'_>]| prefix.iter().map(Span::width).sum::<usize>();
+                let width = usize::from(width);
+                wrap::wrap_tagged(
+                    &content,
+                    width.saturating_sub(prefix_width(&first_prefix)),
+                    width.saturating_sub(prefix_width(&rest_prefix)),
+                )
+            }
+        };
+
+        for (i, line_spans) in wrapped.into_iter().enumerate() {
+            let prefix = if i == 0 { &first_prefix } else { &rest_prefix };
+            self.push_tagged_line(prefix, line_spans);
+        }
+    }
+
+    /// Push a line of `prefix` followed by `spans`, recording where each
+    /// tagged span is drawn.
+    fn push_tagged_line(&mut self, prefix: &[Span<'static>], spans: Cell) {
+        let mut line = prefix.to_vec();
+        let mut column: usize = line.iter().map(Span::width).sum();
+        for (span, target) in spans {
+            let width = span.width();
+            if let Some(target) = target {
+                self.record_position(target, column..column + width);
+            }
+            column += width;
+            line.push(span);
         }
+        self.lines.push(Line::from(line));
+    }
+
+    /// Note that part of a link or footnote reference is drawn at `columns`
+    /// of the line about to be pushed.
+    fn record_position(&mut self, target: Target, columns: Range<usize>) {
+        let line = self.lines.len();
+        let positions = match target {
+            Target::Link(link) => &mut self.links[link].positions,
+            Target::Footnote(note) => &mut self.footnotes[note].references,
+        };
+        match positions.last_mut() {
+            Some(last) if last.line == line && last.columns.end == columns.start => {
+                last.columns.end = columns.end;
+            }
+            _ => positions.push(LinkPosition { line, columns }),
+        }
+    }
+
+    /// Blockquote bars followed by the hanging indent of the innermost list
+    /// item: what a line continuing the current block starts with.
+    fn continuation_prefix(&self) -> Vec<Span<'static>> {
+        let mut spans = Vec::new();
+        self.add_blockquote_prefix(&mut spans);
+        if let Some(&indent) = self.item_indents.last()
+            && indent > 0
+        {
+            spans.push(Span::raw(" ".repeat(indent)));
+        }
+        spans
+    }
+
+    /// Begin a new line of the current block with its continuation prefix.
+    fn start_continuation(&mut self) {
+        self.current_spans = self.continuation_prefix();
+        self.prefix_len = self.current_spans.len();
+        self.link_start = self.prefix_len;
     }
 
     fn push_blank_line(&mut self) {
@@ -521,56 +1282,145 @@ impl Renderer {
     }
 
     fn add_blockquote_prefix(&self, spans: &mut Vec<Span<'static>>) {
-        for _ in 0..self.blockquote_depth {
-            spans.push(Span::styled(
-                "▌ ",
-                Style::default().fg(Color::DarkGray),
-            ));
+        for kind in &self.blockquotes {
+            let style = match kind {
+                Some(kind) => self.alert_style(*kind),
+                None => self.theme.blockquote_bar,
+            };
+            spans.push(Span::styled("▌ ", style));
         }
     }
 
+    fn alert_style(&self, kind: BlockQuoteKind) -> Style {
+        match kind {
+            BlockQuoteKind::Note => self.theme.alert_note,
+            BlockQuoteKind::Tip => self.theme.alert_tip,
+            BlockQuoteKind::Important => self.theme.alert_important,
+            BlockQuoteKind::Warning => self.theme.alert_warning,
+            BlockQuoteKind::Caution => self.theme.alert_caution,
+        }
+    }
 }
 
-fn build_table_row_line(
-    row: &[Vec<Span<'static>>],
-    col_widths: &[usize],
-    is_header: bool,
-) -> Line<'static> {
-    let border_style = Style::default().fg(Color::DarkGray);
-    let mut spans: Vec<Span<'static>> = Vec::new();
-    spans.push(Span::styled("│", border_style));
-
-    for (i, width) in col_widths.iter().enumerate() {
-        let cell_spans = row.get(i).cloned().unwrap_or_default();
-        let content_len: usize = cell_spans.iter().map(|s| s.content.len()).sum();
-        let padding = width.saturating_sub(content_len);
-        let left_pad = padding / 2;
-        let right_pad = padding - left_pad;
-
-        if is_header {
-            let header_style = Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD);
-            let text: String = cell_spans.iter().map(|s| s.content.as_ref()).collect();
-            spans.push(Span::styled(
-                format!(
-                    "{}{}{}",
-                    " ".repeat(left_pad),
-                    text,
-                    " ".repeat(right_pad)
-                ),
-                header_style,
-            ));
-        } else {
-            spans.push(Span::raw(" ".repeat(left_pad)));
-            spans.extend(cell_spans);
-            spans.push(Span::raw(" ".repeat(right_pad)));
-        }
+/// Icon and title line of a GitHub alert such as `> [!NOTE]`.
+fn alert_label(kind: BlockQuoteKind) -> (&'static str, &'static str) {
+    match kind {
+        BlockQuoteKind::Note => ("ℹ", "Note"),
+        BlockQuoteKind::Tip => ("✓", "Tip"),
+        BlockQuoteKind::Important => ("‼", "Important"),
+        BlockQuoteKind::Warning => ("⚠", "Warning"),
+        BlockQuoteKind::Caution => ("✖", "Caution"),
+    }
+}
+
+/// Layer inline styles over the table header style, so a link or `code`
+/// in a header keeps its color and the header's modifiers are added to it.
+fn header_styled(spans: &mut [(Span<'static>, Option<Target>)], theme: &Theme) {
+    for (span, _) in spans {
+        span.style = theme.table_header.patch(span.style);
+    }
+}
+
+/// Narrowest a table column may be squeezed to, padding included.
+const MIN_COLUMN_WIDTH: usize = 6;
+
+/// Shrink natural column widths so the table, borders included, fits in
+/// `width`. Each column gives up space in proportion to how far it is above
+/// [`MIN_COLUMN_WIDTH`]. Returns `None` if even minimal columns don't fit.
+fn fit_column_widths(natural: &[usize], width: usize) -> Option<Vec<usize>> {
+    let available = width.checked_sub(natural.len() + 1)?;
+    let total: usize = natural.iter().sum();
+    if total <= available {
+        return Some(natural.to_vec());
+    }
 
-        spans.push(Span::styled("│", border_style));
+    let minimums: Vec<usize> = natural.iter().map(|&w| w.min(MIN_COLUMN_WIDTH)).collect();
+    let min_total: usize = minimums.iter().sum();
+    if min_total > available {
+        return None;
     }
 
-    Line::from(spans)
+    let spare = available - min_total;
+    let wanted = total - min_total;
+    let mut widths: Vec<usize> = natural
+        .iter()
+        .zip(&minimums)
+        .map(|(&n, &m)| m + (n - m) * spare / wanted)
+        .collect();
+
+    // Hand out the columns lost to rounding, most-shrunk column first
+    let mut leftover = available - widths.iter().sum::<usize>();
+    while leftover > 0 {
+        let Some(i) = (0..widths.len())
+            .filter(|&i| widths[i] < natural[i])
+            .max_by_key(|&i| natural[i] - widths[i])
+        else {
+            break;
+        };
+        widths[i] += 1;
+        leftover -= 1;
+    }
+
+    Some(widths)
+}
+
+/// Lay out one table row, wrapping each cell to its column width and
+/// aligning it as the delimiter row asks (left when unspecified). Returns the
+/// tagged spans of one line per physical row, as tall as the tallest cell.
+fn build_table_row_lines(
+    row: &[Cell],
+    col_widths: &[usize],
+    alignments: &[Alignment],
+    is_header: bool,
+    theme: &Theme,
+) -> Vec<Cell> {
+    let border_style = theme.table_border;
+    let pad_style = if is_header {
+        theme.table_header
+    } else {
+        Style::default()
+    };
+
+    let cells: Vec<Vec<Cell>> = col_widths
+        .iter()
+        .enumerate()
+        .map(|(i, width)| {
+            let mut cell_spans = row.get(i).cloned().unwrap_or_default();
+            if is_header {
+                header_styled(&mut cell_spans, theme);
+            }
+            let inner = width.saturating_sub(2);
+            wrap::wrap_tagged(&cell_spans, inner, inner)
+        })
+        .collect();
+    let height = cells.iter().map(Vec::len).max().unwrap_or(1);
+
+    (0..height)
+        .map(|k| {
+            let mut spans: Cell = Vec::new();
+            spans.push((Span::styled("│", border_style), None));
+
+            for (i, (cell, width)) in cells.iter().zip(col_widths).enumerate() {
+                let cell_spans = cell.get(k).cloned().unwrap_or_default();
+                let content_width: usize = cell_spans.iter().map(|(span, _)| span.width()).sum();
+                let padding = width.saturating_sub(content_width);
+                // One column of padding on each side, the rest per alignment
+                let left_pad = match alignments.get(i).copied().unwrap_or(Alignment::None) {
+                    Alignment::Center => padding / 2,
+                    Alignment::Right => padding.saturating_sub(1),
+                    Alignment::Left | Alignment::None => padding.min(1),
+                };
+                let right_pad = padding - left_pad;
+
+                spans.push((Span::styled(" ".repeat(left_pad), pad_style), None));
+                spans.extend(cell_spans);
+                spans.push((Span::styled(" ".repeat(right_pad), pad_style), None));
+                spans.push((Span::styled("│", border_style), None));
+            }
+
+            spans
+        })
+        .collect()
 }
 
 fn build_table_border(col_widths: &[usize], left: char, mid: char, right: char) -> String {
@@ -597,14 +1447,31 @@ fn line_is_blank(line: &Line<'_>) -> bool {
         .all(|s| s.content.as_ref().trim().is_empty())
 }
 
+impl Default for Renderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Render markdown text into styled ratatui lines.
 pub fn render(input: &str) -> Vec<Line<'static>> {
     Renderer::new().render(input)
 }
 
+/// Render markdown text wrapped to `width` columns. See [`Renderer::width`].
+pub fn render_with_width(input: &str, width: u16) -> Vec<Line<'static>> {
+    Renderer::new().width(width).render(input)
+}
+
+/// Render markdown text into styled ratatui lines using a custom [`Theme`].
+pub fn render_with_theme(input: &str, theme: &Theme) -> Vec<Line<'static>> {
+    Renderer::new().theme(theme.clone()).render(input)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::front_matter::FrontMatterFormat;
 
     /// Helper: extract all text from a line
     fn line_text(line: &Line<'_>) -> String {
@@ -694,6 +1561,48 @@ mod tests {
         assert!(all_text.contains("quoted text"));
     }
 
+    #[test]
+    fn github_alerts() {
+        let input = "> [!WARNING]\n> Back up first.\n>\n\
+                     > - one\n> - two\n>\n> ```\n> rm -rf\n> ```";
+        let lines = Renderer::new().render(input);
+        let texts: Vec<String> = lines.iter().map(line_text).collect();
+        assert_eq!(texts[0], "▌ ⚠ Warning");
+        assert_eq!(texts[1], "▌ Back up first.");
+        assert!(texts.contains(&"▌ • one".to_string()));
+        assert!(texts.iter().any(|t| t.starts_with("▌ ") && t.contains("rm -rf")));
+
+        let theme = Theme::default();
+        for line in lines.iter().filter(|l| !line_is_blank(l)) {
+            assert_eq!(line.spans[0].style, theme.alert_warning);
+        }
+        assert!(!line_has_modifier(&lines[1], Modifier::ITALIC));
+    }
+
+    #[test]
+    fn alerts_nest_inside_quotes() {
+        let lines = render("> Quoted\n>\n> > [!TIP]\n> > Try this.");
+        let texts: Vec<String> = lines.iter().map(line_text).collect();
+        assert_eq!(texts, ["▌ Quoted", "", "▌ ▌ ✓ Tip", "▌ ▌ Try this."]);
+        let theme = Theme::default();
+        assert_eq!(lines[3].spans[0].style, theme.blockquote_bar);
+        assert_eq!(lines[3].spans[1].style, theme.alert_tip);
+    }
+
+    #[test]
+    fn code_in_list_items_keeps_the_hanging_indent() {
+        let texts: Vec<String> = render("- item\n\n  ```\n  let x = 1;\n  ```")
+            .iter()
+            .map(line_text)
+            .collect();
+        assert_eq!(texts[2], "      let x = 1;");
+
+        let input = "- item\n\n  > [!NOTE]\n  > ```\n  > code\n  > ```";
+        let texts: Vec<String> = render(input).iter().map(line_text).collect();
+        assert_eq!(texts[2], "▌   ℹ Note");
+        assert_eq!(texts[3], "▌       code");
+    }
+
     #[test]
     fn unordered_list() {
         let lines = render("- first\n- second\n- third");
@@ -816,8 +1725,542 @@ mod tests {
         }
     }
 
+    // ── Theme tests ──
+
+    #[test]
+    fn custom_theme_styles_headings_and_code() {
+        let mut theme = Theme::default();
+        theme.headings[0] = Style::default().fg(Color::Red);
+        theme.inline_code = Style::default().fg(Color::LightGreen);
+        let lines = render_with_theme("# Title\n\nuse `code`", &theme);
+        assert!(line_has_fg(&lines[0], Color::Red));
+        assert!(lines.iter().any(|l| line_has_fg(l, Color::LightGreen)));
+    }
+
+    #[test]
+    fn monochrome_theme_emits_no_colors_for_prose() {
+        let lines = render_with_theme(
+            "# Title\n\n- [x] done\n\n> quote\n\n[link](https://example.com)",
+            &Theme::monochrome(),
+        );
+        for line in &lines {
+            assert!(line.spans.iter().all(|s| s.style.fg.is_none()));
+        }
+    }
+
+    #[test]
+    fn color_depth_none_strips_all_colors() {
+        let lines = Renderer::new()
+            .color_depth(ColorDepth::None)
+            .render("# Title\n\n```rust\nlet x = 1;\n```\n\n| a | b |\n|---|---|\n| 1 | 2 |");
+        for line in &lines {
+            assert!(line.spans.iter().all(|s| s.style.fg.is_none() && s.style.bg.is_none()));
+        }
+    }
+
+    #[cfg(feature = "syntect")]
+    #[test]
+    fn syntax_theme_and_syntaxes_configure_one_highlighter() {
+        let lines = Renderer::new()
+            .syntax_theme(SyntaxTheme::named("InspiredGitHub").unwrap())
+            .syntaxes(Syntaxes::default().alias("rustlang", "Rust"))
+            .code_background(CodeBackground::SyntaxTheme)
+            .render("```rustlang\nfn main() {}\n```");
+        let code = &lines[1].spans[1..];
+        let white = Some(Color::Rgb(255, 255, 255));
+        assert!(code.iter().all(|s| s.style.bg == white));
+        assert!(code.iter().any(|s| s.style.fg != code[0].style.fg));
+    }
+
+    // ── Code block language tests ──
+
+    #[test]
+    fn unlabeled_code_block_language_is_inferred() {
+        let output = Renderer::new()
+            .guess_languages(true)
+            .render_output("text\n\n```\n{\"a\": 1}\n```");
+        assert_eq!(output.code_blocks.len(), 1);
+        let block = &output.code_blocks[0];
+        assert_eq!(block.info.language, "");
+        assert_eq!(block.inferred_language.as_deref(), Some("json"));
+        assert!(line_text(&output.lines[block.line]).contains("{\"a\": 1}"));
+    }
+
+    #[test]
+    fn language_guessing_is_off_by_default() {
+        let output = Renderer::new().render_output("```\n{\"a\": 1}\n```");
+        assert_eq!(output.code_blocks[0].inferred_language, None);
+    }
+
+    #[test]
+    fn fenced_language_is_not_guessed() {
+        let output = Renderer::new().render_output("```rust\n{\"a\": 1}\n```");
+        assert_eq!(output.code_blocks[0].info.language, "rust");
+        assert_eq!(output.code_blocks[0].inferred_language, None);
+        assert_eq!(line_text(&output.lines[output.code_blocks[0].line]).trim(), "rust");
+    }
+
+    #[test]
+    fn info_string_attributes_are_parsed() {
+        let output = Renderer::new().render_output("```rust,ignore\nfn main() {}\n```");
+        let info = &output.code_blocks[0].info;
+        assert_eq!(info.language, "rust");
+        assert!(info.has_flag("ignore"));
+        assert_eq!(line_text(&output.lines[0]).trim(), "rust");
+    }
+
+    #[test]
+    fn title_attribute_replaces_language_label() {
+        let output = Renderer::new().render_output("```python title=\"app.py\"\nprint(1)\n```");
+        assert_eq!(line_text(&output.lines[0]).trim(), "app.py");
+        assert_eq!(output.code_blocks[0].info.language, "python");
+    }
+
+    #[test]
+    fn pandoc_class_selects_language() {
+        let output = Renderer::new().render_output("```{.js}\nlet x = 1;\n```");
+        assert_eq!(output.code_blocks[0].info.language, "js");
+        assert_eq!(line_text(&output.lines[0]).trim(), "js");
+    }
+
+    #[test]
+    fn startline_numbers_code_block() {
+        let output = Renderer::new().render_output("```text startline=120 {2}\na\nb\n```");
+        let first = output.code_blocks[0].line + 1;
+        assert_eq!(line_text(&output.lines[first]), "120 a");
+        assert_eq!(line_text(&output.lines[first + 1]), "121 b");
+        let highlight_bg = Theme::default().code_line_highlight.bg;
+        assert!(output.lines[first + 1].spans.iter().all(|s| s.style.bg == highlight_bg));
+    }
+
     // ── Edge case tests ──
 
+    #[test]
+    fn wrapped_list_items_hang_under_their_text() {
+        let lines = render_with_width("- alpha beta gamma delta", 14);
+        let texts: Vec<String> = lines.iter().map(line_text).collect();
+        assert_eq!(texts[0], "• alpha beta");
+        assert_eq!(texts[1], "  gamma delta");
+    }
+
+    #[test]
+    fn wrapped_blockquotes_repeat_the_bar() {
+        let lines = render_with_width("> one two three four", 11);
+        let texts: Vec<String> = lines.iter().map(line_text).collect();
+        assert_eq!(texts[0], "▌ one two");
+        assert_eq!(texts[1], "▌ three");
+        assert_eq!(texts[2], "▌ four");
+    }
+
+    #[test]
+    fn soft_breaks_keep_container_prefixes() {
+        let lines = render("> first\n> second\n\n1. item\n   more");
+        let texts: Vec<String> = lines.iter().map(line_text).collect();
+        assert!(texts.contains(&"▌ second".to_string()));
+        assert!(texts.contains(&"   more".to_string()));
+    }
+
+    #[test]
+    fn no_line_exceeds_width() {
+        let input = "# A heading that is long\n\n> - quoted list item that wraps around\n\n\
+                     Plain paragraph with a verylongwordthatcannotfit in it.";
+        for line in render_with_width(input, 12) {
+            assert!(line.width() <= 12, "{:?}", line_text(&line));
+        }
+    }
+
+    #[test]
+    fn narrow_tables_wrap_cells_inside_borders() {
+        let input = "| Name | Description |\n|---|---|\n\
+                     | tui-md | Renders markdown into ratatui lines with themes |";
+        let lines = render_with_width(input, 30);
+        let table: Vec<&Line<'_>> = lines.iter().take_while(|l| !line_is_blank(l)).collect();
+        assert!(table.len() > 5, "expected wrapped rows");
+        for line in &table {
+            assert_eq!(line.width(), 30, "{:?}", line_text(line));
+        }
+        let text: String = table.iter().map(|l| line_text(l)).collect();
+        assert!(text.contains("Renders"));
+        assert!(text.contains("themes"));
+    }
+
+    #[test]
+    fn wide_enough_tables_keep_natural_widths() {
+        let input = "| A | B |\n|---|---|\n| 1 | 2 |";
+        assert_eq!(render(input), render_with_width(input, 80));
+    }
+
+    #[test]
+    fn very_narrow_tables_fall_back_to_records() {
+        let input = "| Name | Role | Team |\n|---|---|---|\n\
+                     | Ada | Dev | Core |\n| Bob | Ops | Infra |";
+        let lines = render_with_width(input, 16);
+        let texts: Vec<String> = lines.iter().map(line_text).collect();
+        assert_eq!(&texts[..3], ["Name: Ada", "Role: Dev", "Team: Core"]);
+        assert!(texts[3].starts_with('─'));
+        assert_eq!(texts[4], "Name: Bob");
+        assert!(lines.iter().all(|l| l.width() <= 16));
+    }
+
+    #[test]
+    fn tables_in_lists_and_quotes_fit_beside_the_prefix() {
+        let input = "- item\n\n  | Name | Description |\n  |---|---|\n  | a | one two three |\n\n\
+                     > | Name | Role | Team |\n> |---|---|---|\n> | Ada | Dev | Core |";
+        let lines = render_with_width(input, 20);
+        let texts: Vec<String> = lines.iter().map(line_text).collect();
+        assert!(texts[2].starts_with("  ┌"));
+        let grid: Vec<&Line<'_>> = lines[2..].iter().take_while(|l| !line_is_blank(l)).collect();
+        assert!(grid.iter().all(|l| l.width() == 20), "{:?}", texts);
+        let quote = texts.iter().position(|t| t.starts_with("▌ Name:")).unwrap();
+        assert_eq!(&texts[quote..], ["▌ Name: Ada", "▌ Role: Dev", "▌ Team: Core"]);
+    }
+
+    #[test]
+    fn table_columns_follow_alignment() {
+        let input = "| L | C | R |\n|:--|:-:|--:|\n\
+                     | a | b | c |\n| long | long | long |";
+        let lines = render(input);
+        assert_eq!(line_text(&lines[3]), "│ a    │  b   │    c │");
+        assert_eq!(line_text(&lines[4]), "│ long │ long │ long │");
+    }
+
+    #[test]
+    fn table_borders_line_up_with_wide_characters() {
+        let input = "| 名前 | Emoji |\n|---|---|\n| 日本語テキスト | 🎉 |\n| café | ok |";
+        let lines = render(input);
+        let table: Vec<&Line<'_>> = lines.iter().take_while(|l| !line_is_blank(l)).collect();
+        let width = table[0].width();
+        assert!(table.iter().all(|l| l.width() == width));
+    }
+
+    #[test]
+    fn table_cells_keep_inline_formatting() {
+        let input = "| `code` **Head** |\n|---|\n| [docs](https://x.io) ![logo](l.png) ~~old~~ |";
+        let lines = render(input);
+        let theme = Theme::default();
+        let header = &lines[1];
+        let code = header.spans.iter().find(|s| s.content == "code").unwrap();
+        assert_eq!(code.style.fg, theme.inline_code.fg);
+        assert!(code.style.add_modifier.contains(Modifier::BOLD));
+
+        let body = &lines[3];
+        assert_line_contains(body, "docs (https://x.io)");
+        assert_line_contains(body, "[img: logo]");
+        let link = body.spans.iter().find(|s| s.content == "docs").unwrap();
+        assert_eq!(link.style.fg, theme.link.fg);
+        let old = body.spans.iter().find(|s| s.content == "old").unwrap();
+        assert!(old.style.add_modifier.contains(Modifier::CROSSED_OUT));
+        // Nothing leaked into the text after the table
+        assert!(lines[5..].iter().all(line_is_blank));
+    }
+
+    #[test]
+    fn outline_records_headings_and_lines() {
+        let input = "# Intro\n\ntext\n\n## Using `tui-md`\n\n## Intro";
+        let output = Renderer::new().render_output(input);
+        let outline = &output.outline;
+        assert_eq!(outline.len(), 3);
+        assert_eq!((outline[0].level, outline[0].text.as_str()), (1, "Intro"));
+        assert_eq!(outline[1].text, "Using tui-md");
+        assert_eq!(outline[1].slug, "using-tui-md");
+        assert_eq!(outline[2].slug, "intro-1");
+        for heading in outline {
+            assert_eq!(line_text(&output.lines[heading.line]), heading.text);
+        }
+    }
+
+    #[test]
+    fn links_are_recorded_with_positions() {
+        let input = "See [the docs](https://docs.rs \"Docs\") and [`api`](#api).";
+        let output = Renderer::new().render_output(input);
+        let links = &output.links;
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0].url, "https://docs.rs");
+        assert_eq!(links[0].title, "Docs");
+        assert_eq!(links[0].text, "the docs");
+        assert_eq!(links[1].text, "api");
+
+        let text = line_text(&output.lines[0]);
+        let position = &links[0].positions[0];
+        assert_eq!(position.line, 0);
+        assert_eq!(&text[position.columns.clone()], "the docs");
+        assert_eq!(crate::link_at(links, 0, 4), Some(0));
+        assert_eq!(crate::link_at(links, 0, 0), None);
+    }
+
+    #[test]
+    fn wrapped_links_have_a_position_per_line() {
+        let input = "> Read [the full user guide](https://example.com/guide) today";
+        let output = Renderer::new().width(20).render_output(input);
+        let link = &output.links[0];
+        assert!(link.positions.len() > 1);
+        for position in &link.positions {
+            let line = &output.lines[position.line];
+            let chars: Vec<char> = line_text(line).chars().collect();
+            let covered: String = chars[position.columns.clone()].iter().collect();
+            assert!(!covered.trim().is_empty());
+            assert!(!covered.contains('▌'));
+        }
+    }
+
+    #[test]
+    fn links_in_tables_are_positioned_when_laid_out() {
+        let input = "> | Site | Ref |\n> |---|---|\n\
+                     > | [docs](https://docs.rs) | x[^n] |\n\n[^n]: Note";
+        let covered = |output: &RenderOutput, position: &LinkPosition| -> String {
+            let chars: Vec<char> = line_text(&output.lines[position.line]).chars().collect();
+            chars[position.columns.clone()].iter().collect()
+        };
+
+        let output = Renderer::new().render_output(input);
+        let position = &output.links[0].positions[0];
+        assert_eq!(covered(&output, position), "docs");
+        assert_eq!(covered(&output, &output.footnotes[0].references[0]), "¹");
+
+        // The record fallback for narrow widths
+        let output = Renderer::new().width(12).render_output(input);
+        let positions = &output.links[0].positions;
+        let text: String = positions.iter().map(|p| covered(&output, p)).collect();
+        assert_eq!(text, "docs");
+        assert!(line_text(&output.lines[positions[0].line]).starts_with("▌ Site: "));
+    }
+
+    #[test]
+    fn link_display_modes() {
+        let input = "[one](https://a.io) and [two](https://b.io) and [1](https://a.io)";
+        let first_line = |display| {
+            let lines = Renderer::new().link_display(display).render(input);
+            line_text(&lines[0])
+        };
+        assert_eq!(first_line(LinkDisplay::Hidden), "one and two and 1");
+        assert_eq!(first_line(LinkDisplay::Hyperlink), "one and two and 1");
+        assert_eq!(first_line(LinkDisplay::Numbered), "one[1] and two[2] and 1[1]");
+    }
+
+    #[test]
+    fn numbered_links_are_listed_at_the_end() {
+        let output = Renderer::new()
+            .link_display(LinkDisplay::Numbered)
+            .render_output("See [the docs](https://docs.rs/tui-md) for more.");
+        let texts: Vec<String> = output.lines.iter().map(line_text).collect();
+        assert_eq!(
+            texts,
+            ["See the docs[1] for more.", "", "Links", "", "[1] https://docs.rs/tui-md"]
+        );
+        assert_eq!(output.links[0].positions[0].columns, 4..12);
+        assert_eq!(output.links[1].url, "https://docs.rs/tui-md");
+        assert_eq!(output.links[1].positions[0].line, 4);
+        assert_eq!(output.links[1].positions[0].columns, 4..26);
+        assert!(output.outline.is_empty());
+    }
+
+    #[test]
+    fn footnotes_are_numbered_and_listed_at_the_end() {
+        let input = "Alpha[^b] and beta[^a], alpha again[^b].\n\n\
+                     [^a]: Note A.\n\n[^b]: Note B\n    with two paragraphs.\n\n    Second.";
+        let output = Renderer::new().render_output(input);
+        let texts: Vec<String> = output.lines.iter().map(line_text).collect();
+        assert_eq!(texts[0], "Alpha¹ and beta², alpha again¹.");
+        assert_eq!(
+            &texts[2..],
+            [
+                "Footnotes",
+                "",
+                "1. Note B",
+                "   with two paragraphs.",
+                "",
+                "   Second.",
+                "",
+                "2. Note A.",
+            ]
+        );
+
+        let notes = &output.footnotes;
+        assert_eq!(notes.len(), 2);
+        assert_eq!(notes[0].label, "b");
+        assert_eq!(notes[0].line, 4);
+        assert_eq!(notes[1].line, 9);
+        let columns: Vec<Range<usize>> =
+            notes[0].references.iter().map(|p| p.columns.clone()).collect();
+        assert_eq!(columns, [5..6, 29..30]);
+        assert_eq!(crate::footnote_at(notes, 0, 15), Some(1));
+    }
+
+    #[test]
+    fn footnotes_opening_with_a_block_keep_their_number() {
+        let input = "A[^a] B[^b] C[^c] D[^d]\n\n[^a]: - item\n\n[^b]: ```\n    code\n    ```\n\n\
+                     [^c]: > quoted\n\n[^d]: | h |\n    |---|\n    | 1 |";
+        let output = Renderer::new().render_output(input);
+        let texts: Vec<String> = output.lines.iter().map(line_text).collect();
+        let notes = &output.footnotes;
+        assert_eq!(notes.len(), 4);
+        for (i, note) in notes.iter().enumerate() {
+            assert_eq!(texts[note.line], format!("{}.", i + 1));
+        }
+        assert_eq!(texts[notes[0].line + 1], "• item");
+        assert_eq!(texts[notes[1].line + 1].trim(), "code");
+        assert_eq!(texts[notes[2].line + 1], "▌    quoted");
+        assert!(texts[notes[3].line + 1].starts_with("   ┌"));
+    }
+
+    #[test]
+    fn unmatched_footnotes_are_left_out() {
+        let output = Renderer::new().render_output("Text[^missing].\n\n[^unused]: Never cited.");
+        assert!(output.footnotes.is_empty());
+        let texts: Vec<String> = output.lines.iter().map(line_text).collect();
+        assert_eq!(texts, ["Text[^missing]."]);
+    }
+
+    #[test]
+    fn inline_and_display_math() {
+        let input = "Energy $E = mc^2$ and $\\weird$.\n\n\
+                     Solve $$\\frac{a}{b}$$ then stop.";
+        let lines = Renderer::new().render(input);
+        let texts: Vec<String> = lines.iter().map(line_text).collect();
+        assert_eq!(
+            texts,
+            ["Energy E = mc² and $\\weird$.", "", "Solve", "    a", "    ─", "    b", "then stop."]
+        );
+        let theme = Theme::default();
+        let raw = lines[0].spans.iter().find(|s| s.content == "$\\weird$").unwrap();
+        assert_eq!(raw.style, theme.math_raw);
+        assert_eq!(lines[3].spans[0].style, theme.math);
+    }
+
+    #[test]
+    fn math_can_be_turned_off_for_prices() {
+        let input = "From $5-$10 each";
+        assert_eq!(line_text(&Renderer::new().math(false).render(input)[0]), input);
+        assert_ne!(line_text(&Renderer::new().render(input)[0]), input);
+    }
+
+    #[test]
+    fn only_text_after_display_math_is_trimmed() {
+        let lines = render("Solve $$x$$   then &nbsp;more\n\n&nbsp;&nbsp;next");
+        let texts: Vec<String> = lines.iter().map(line_text).collect();
+        assert_eq!(texts, ["Solve", "    x", "then \u{a0}more", "", "\u{a0}\u{a0}next"]);
+
+        let lines = render("&nbsp;&nbsp;indented\n\n- &nbsp;item");
+        assert_eq!(line_text(&lines[0]), "\u{a0}\u{a0}indented");
+        assert_eq!(line_text(&lines[2]), "• \u{a0}item");
+    }
+
+    #[test]
+    fn front_matter_display_modes() {
+        let input = "---\ntitle: Release notes\nauthor: Ada\ndate: 2024-05-01\n\
+                     tags: [news, rust]\n---\n\
+                     \n# Changes\n\nBody text.\n\n---\n\nAfter the rule.";
+        let texts = |display| {
+            let output = Renderer::new().front_matter_display(display).render_output(input);
+            let matter = output.front_matter.expect("front matter is parsed in every mode");
+            assert_eq!(matter.get("author").map(ToString::to_string).as_deref(), Some("Ada"));
+            output.lines.iter().map(line_text).collect::<Vec<_>>()
+        };
+
+        let hidden = texts(FrontMatterDisplay::Hidden);
+        assert_eq!(hidden[0], "Changes");
+        assert!(hidden.iter().any(|t| t.starts_with('─')));
+        assert!(hidden.contains(&"After the rule.".to_string()));
+        assert_eq!(hidden.len(), Renderer::new().render(&input[input.find("# ").unwrap()..]).len());
+
+        let card = texts(FrontMatterDisplay::Card);
+        assert_eq!(card[..4], ["Release notes", "Ada · 2024-05-01", "#news #rust", ""]);
+
+        let table = texts(FrontMatterDisplay::Table);
+        assert!(table[0].starts_with('┌'));
+        assert!(table.iter().any(|t| t.contains("title") && t.contains("Release notes")));
+        assert!(table.iter().any(|t| t.contains("tags") && t.contains("news, rust")));
+    }
+
+    #[test]
+    fn multi_line_front_matter_values() {
+        let input = "---\ntitle: |\n  Release\n  notes\nauthor: Ada\n---\nBody";
+        let texts = |display| {
+            let lines = Renderer::new().front_matter_display(display).render(input);
+            lines.iter().map(line_text).collect::<Vec<_>>()
+        };
+        assert_eq!(texts(FrontMatterDisplay::Card)[..2], ["Release notes", "Ada"]);
+        let table = texts(FrontMatterDisplay::Table);
+        assert_eq!(table[3], "│ title  │ Release │");
+        assert_eq!(table[4], "│        │ notes   │");
+        assert_eq!(table[5], "│ author │ Ada     │");
+    }
+
+    #[test]
+    fn metadata_fences_later_in_the_document_render_as_before() {
+        let input = "Intro\n\n---\nfoo: bar\n---\n\nEnd";
+        let output = Renderer::new().render_output(input);
+        assert!(output.front_matter.is_none());
+        let texts: Vec<String> = output.lines.iter().map(line_text).collect();
+        assert!(texts[2].starts_with('─'));
+        assert_eq!(output.outline[0].text, "foo: bar");
+        assert_eq!(texts.last().map(String::as_str), Some("End"));
+    }
+
+    #[test]
+    fn fences_later_in_the_document_keep_their_references() {
+        let input = "Intro\n\n---\nsee [docs]\n---\n\n[docs]: https://x.y";
+        let output = Renderer::new().render_output(input);
+        assert!(output.front_matter.is_none());
+        assert_eq!(output.outline[0].text, "see docs");
+        assert_eq!(output.links[0].url, "https://x.y");
+    }
+
+    #[test]
+    fn yaml_front_matter_may_close_with_dots() {
+        let output = Renderer::new().render_output("---\ntitle: Notes\n...\n\nText");
+        let matter = output.front_matter.unwrap();
+        assert_eq!(matter.get("title").map(ToString::to_string).as_deref(), Some("Notes"));
+        assert_eq!(line_text(&output.lines[0]), "Text");
+    }
+
+    #[test]
+    fn toml_front_matter() {
+        let output = Renderer::new().render_output("+++\ntitle = \"Notes\"\n+++\n\nText");
+        let matter = output.front_matter.unwrap();
+        assert_eq!(matter.format, FrontMatterFormat::Toml);
+        assert_eq!(matter.get("title").map(ToString::to_string).as_deref(), Some("Notes"));
+        assert_eq!(line_text(&output.lines[0]), "Text");
+    }
+
+    #[test]
+    fn heading_anchors_resolve_fragment_links() {
+        let input = "# Intro\n\nSee [setup](#installation), [api](#api) and [gone](#nope).\n\n\
+                     ## Installation\n\nText.\n\n## Reference {#api}\n\n## Api";
+        let output = Renderer::new().render_output(input);
+        let texts: Vec<String> = output.lines.iter().map(line_text).collect();
+        assert!(texts.contains(&"Reference".to_string()));
+
+        let slugs: Vec<&str> = output.outline.iter().map(|h| h.slug.as_str()).collect();
+        assert_eq!(slugs, ["intro", "installation", "api", "api-1"]);
+        assert_eq!(output.anchors["installation"], output.outline[1].line);
+        assert_eq!(output.resolve_link(&output.links[0]), Some(output.outline[1].line));
+        assert_eq!(output.resolve_link(&output.links[1]), Some(output.outline[2].line));
+        assert_eq!(output.broken_links(), [2]);
+    }
+
+    #[test]
+    fn fragment_links_show_only_their_text() {
+        let input = "See [setup](#setup) and [docs](https://docs.rs).\n\n## Setup";
+        let output = Renderer::new().render_output(input);
+        assert_eq!(line_text(&output.lines[0]), "See setup and docs (https://docs.rs).");
+
+        let output = Renderer::new()
+            .link_display(LinkDisplay::Numbered)
+            .render_output(input);
+        assert_eq!(line_text(&output.lines[0]), "See setup and docs[1].");
+        assert_eq!(output.resolve_link(&output.links[0]), Some(output.outline[0].line));
+    }
+
+    #[test]
+    fn explicit_ids_leave_earlier_slugs_alone() {
+        let output = Renderer::new().render_output("## Api\n\n## Reference {#api}\n\n## Api");
+        let slugs: Vec<&str> = output.outline.iter().map(|h| h.slug.as_str()).collect();
+        assert_eq!(slugs, ["api", "api", "api-1"]);
+        assert_eq!(output.anchors["api"], output.outline[0].line);
+        assert_eq!(output.anchors["api-1"], output.outline[2].line);
+    }
+
     #[test]
     fn empty_input() {
         let lines = render("");
@@ -0,0 +1,334 @@
+use ratatui::prelude::*;
+use std::ops::Range;
+use unicode_width::UnicodeWidthChar;
+
+use crate::Theme;
+use crate::view::MarkdownViewState;
+use crate::wrap::text_width;
+
+/// What to look for in rendered text.
+///
+/// Matching runs against the text as displayed, so markup such as `**` or
+/// link destinations hidden by the renderer never gets in the way.
+#[derive(Debug, Clone)]
+pub struct SearchQuery {
+    pattern: Pattern,
+    ignore_case: bool,
+}
+
+#[derive(Debug, Clone)]
+enum Pattern {
+    Literal(String),
+    #[cfg(feature = "regex")]
+    Regex(regex::Regex),
+}
+
+/// A match in the rendered lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchMatch {
+    /// Index into the rendered lines.
+    pub line: usize,
+    /// Display columns covered by the match.
+    pub columns: Range<usize>,
+}
+
+impl SearchQuery {
+    /// Search for `text` literally, case-sensitively.
+    pub fn new(text: &str) -> Self {
+        Self {
+            pattern: Pattern::Literal(text.to_string()),
+            ignore_case: false,
+        }
+    }
+
+    /// Search with a regular expression. Case sensitivity is set with
+    /// [`SearchQuery::ignore_case`] rather than with a `(?i)` flag, though
+    /// both work.
+    #[cfg(feature = "regex")]
+    pub fn regex(pattern: &str) -> Result<Self, regex::Error> {
+        Ok(Self {
+            pattern: Pattern::Regex(regex::Regex::new(pattern)?),
+            ignore_case: false,
+        })
+    }
+
+    /// Match regardless of case.
+    pub fn ignore_case(mut self, ignore_case: bool) -> Self {
+        #[cfg(feature = "regex")]
+        if let Pattern::Regex(regex) = &self.pattern
+            && let Ok(rebuilt) = regex::RegexBuilder::new(regex.as_str())
+                .case_insensitive(ignore_case)
+                .build()
+        {
+            self.pattern = Pattern::Regex(rebuilt);
+        }
+        self.ignore_case = ignore_case;
+        self
+    }
+
+    /// Every match in `lines`, in order. Matches never span lines.
+    pub fn find(&self, lines: &[Line<'_>]) -> Vec<SearchMatch> {
+        let mut matches = Vec::new();
+        for (index, line) in lines.iter().enumerate() {
+            let text: String = line.spans.iter().map(|s| s.content.as_ref()).collect();
+            for bytes in self.find_in(&text) {
+                let start = text_width(&text[..bytes.start]);
+                let end = start + text_width(&text[bytes]);
+                matches.push(SearchMatch {
+                    line: index,
+                    columns: start..end,
+                });
+            }
+        }
+        matches
+    }
+
+    /// Byte ranges of the non-empty matches in `text`.
+    fn find_in(&self, text: &str) -> Vec<Range<usize>> {
+        match &self.pattern {
+            Pattern::Literal(needle) => find_literal(text, needle, self.ignore_case),
+            #[cfg(feature = "regex")]
+            Pattern::Regex(regex) => regex
+                .find_iter(text)
+                .map(|m| m.range())
+                .filter(|r| !r.is_empty())
+                .collect(),
+        }
+    }
+}
+
+/// Non-overlapping occurrences of `needle`, compared char by char so that
+/// case folding never shifts byte offsets.
+fn find_literal(text: &str, needle: &str, ignore_case: bool) -> Vec<Range<usize>> {
+    let fold = |c: char| {
+        if ignore_case {
+            c.to_lowercase().next().unwrap_or(c)
+        } else {
+            c
+        }
+    };
+    let needle: Vec<char> = needle.chars().map(fold).collect();
+    if needle.is_empty() {
+        return Vec::new();
+    }
+    let chars: Vec<(usize, char)> = text.char_indices().map(|(i, c)| (i, fold(c))).collect();
+
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    while i + needle.len() <= chars.len() {
+        if chars[i..i + needle.len()].iter().map(|(_, c)| *c).eq(needle.iter().copied()) {
+            let start = chars[i].0;
+            let end = chars.get(i + needle.len()).map_or(text.len(), |(b, _)| *b);
+            ranges.push(start..end);
+            i += needle.len();
+        } else {
+            i += 1;
+        }
+    }
+    ranges
+}
+
+/// Patch the matches in `lines` with [`Theme::search_match`], and the
+/// match at index `current` with [`Theme::search_current`].
+pub fn highlight_matches(
+    lines: &mut [Line<'static>],
+    matches: &[SearchMatch],
+    current: Option<usize>,
+    theme: &Theme,
+) {
+    for (index, m) in matches.iter().enumerate() {
+        let style = if current == Some(index) {
+            theme.search_current
+        } else {
+            theme.search_match
+        };
+        if let Some(line) = lines.get_mut(m.line) {
+            restyle_columns(line, &m.columns, style);
+        }
+    }
+}
+
+/// Patch `style` onto the characters of `line` within `columns`, splitting
+/// spans where the range starts and ends.
+pub(crate) fn restyle_columns(line: &mut Line<'static>, columns: &Range<usize>, style: Style) {
+    let mut spans: Vec<Span<'static>> = Vec::with_capacity(line.spans.len() + 2);
+    let mut column = 0;
+    for span in line.spans.drain(..) {
+        let width = span.width();
+        if column + width <= columns.start || column >= columns.end {
+            column += width;
+            spans.push(span);
+            continue;
+        }
+
+        // Split into runs that are inside or outside the range
+        let mut runs: Vec<(String, bool)> = Vec::new();
+        for c in span.content.chars() {
+            let inside = columns.contains(&column);
+            column += c.width().unwrap_or(0);
+            match runs.last_mut() {
+                Some((text, was_inside)) if *was_inside == inside => text.push(c),
+                _ => runs.push((c.to_string(), inside)),
+            }
+        }
+        for (text, inside) in runs {
+            let run_style = if inside {
+                span.style.patch(style)
+            } else {
+                span.style
+            };
+            spans.push(Span::styled(text, run_style));
+        }
+    }
+    line.spans = spans;
+}
+
+/// Matches of a search and which one is current, for stepping through them
+/// in a [`MarkdownView`](crate::MarkdownView).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchState {
+    matches: Vec<SearchMatch>,
+    current: Option<usize>,
+}
+
+impl SearchState {
+    /// Run `query` over `lines`, which should be laid out at the width the
+    /// view is drawn at. No match is current until [`SearchState::next`].
+    pub fn new(query: &SearchQuery, lines: &[Line<'_>]) -> Self {
+        Self {
+            matches: query.find(lines),
+            current: None,
+        }
+    }
+
+    pub fn matches(&self) -> &[SearchMatch] {
+        &self.matches
+    }
+
+    /// Index into [`SearchState::matches`] of the current match.
+    pub fn current_index(&self) -> Option<usize> {
+        self.current
+    }
+
+    pub fn current(&self) -> Option<&SearchMatch> {
+        self.current.and_then(|i| self.matches.get(i))
+    }
+
+    /// Move to the next match, wrapping around, and scroll it into view. The
+    /// first call picks the first match at or below the top of the view.
+    pub fn next(&mut self, view: &mut MarkdownViewState) -> Option<&SearchMatch> {
+        if self.matches.is_empty() {
+            return None;
+        }
+        let next = match self.current {
+            Some(i) => (i + 1) % self.matches.len(),
+            None => self
+                .matches
+                .iter()
+                .position(|m| m.line >= view.offset())
+                .unwrap_or(0),
+        };
+        self.select(next, view)
+    }
+
+    /// Move to the previous match, wrapping around, and scroll it into view.
+    /// The first call picks the last match above the bottom of the view.
+    pub fn previous(&mut self, view: &mut MarkdownViewState) -> Option<&SearchMatch> {
+        if self.matches.is_empty() {
+            return None;
+        }
+        let last = self.matches.len() - 1;
+        let previous = match self.current {
+            Some(0) => last,
+            Some(i) => i - 1,
+            None => {
+                let bottom = view.offset() + view.viewport_height();
+                self.matches.iter().rposition(|m| m.line < bottom).unwrap_or(last)
+            }
+        };
+        self.select(previous, view)
+    }
+
+    /// Patch the matches into `lines`. See [`highlight_matches`].
+    pub fn highlight(&self, lines: &mut [Line<'static>], theme: &Theme) {
+        highlight_matches(lines, &self.matches, self.current, theme);
+    }
+
+    fn select(&mut self, index: usize, view: &mut MarkdownViewState) -> Option<&SearchMatch> {
+        self.current = Some(index);
+        view.reveal(self.matches[index].line);
+        self.matches.get(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(texts: &[&str]) -> Vec<Line<'static>> {
+        texts.iter().map(|t| Line::from(t.to_string())).collect()
+    }
+
+    #[test]
+    fn finds_literal_matches_with_columns() {
+        let lines = lines(&["foo bar foo", "no", "日本 foo"]);
+        let matches = SearchQuery::new("foo").find(&lines);
+        let found: Vec<(usize, Range<usize>)> =
+            matches.into_iter().map(|m| (m.line, m.columns)).collect();
+        assert_eq!(found, vec![(0, 0..3), (0, 8..11), (2, 5..8)]);
+    }
+
+    #[test]
+    fn case_insensitive_search() {
+        let lines = lines(&["Foo FOO foo"]);
+        assert_eq!(SearchQuery::new("foo").find(&lines).len(), 1);
+        assert_eq!(SearchQuery::new("foo").ignore_case(true).find(&lines).len(), 3);
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn regex_search() {
+        let lines = lines(&["v1.2 and V3.4"]);
+        let query = SearchQuery::regex(r"v\d\.\d").unwrap();
+        assert_eq!(query.find(&lines).len(), 1);
+        assert_eq!(query.ignore_case(true).find(&lines).len(), 2);
+        assert!(SearchQuery::regex("(").is_err());
+    }
+
+    #[test]
+    fn matches_span_styled_text() {
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        let mut lines = vec![Line::from(vec![Span::raw("a bo"), Span::styled("ld b", bold)])];
+        let matches = SearchQuery::new("bold").find(&lines);
+        assert_eq!(matches[0].columns, 2..6);
+
+        let theme = Theme::default();
+        highlight_matches(&mut lines, &matches, None, &theme);
+        let highlighted: String = lines[0]
+            .spans
+            .iter()
+            .filter(|s| s.style.bg == theme.search_match.bg)
+            .map(|s| s.content.as_ref())
+            .collect();
+        assert_eq!(highlighted, "bold");
+        let ld = lines[0].spans.iter().find(|s| s.content == "ld").unwrap();
+        assert!(ld.style.add_modifier.contains(Modifier::BOLD));
+    }
+
+    #[test]
+    fn next_and_previous_wrap_and_scroll() {
+        let texts: Vec<String> = (0..50).map(|i| format!("line {i}")).collect();
+        let lines: Vec<Line<'static>> = texts.iter().map(|t| Line::from(t.clone())).collect();
+        let mut view = MarkdownViewState::default();
+        view.set_heights(50, 10);
+        let mut search = SearchState::new(&SearchQuery::new("line 4"), &lines);
+        assert_eq!(search.matches().len(), 11);
+
+        assert_eq!(search.next(&mut view).map(|m| m.line), Some(4));
+        assert_eq!(view.offset(), 0);
+        assert_eq!(search.next(&mut view).map(|m| m.line), Some(40));
+        assert!(view.offset() <= 40 && 40 < view.offset() + 10);
+        assert_eq!(search.previous(&mut view).map(|m| m.line), Some(4));
+        assert_eq!(search.previous(&mut view).map(|m| m.line), Some(49));
+    }
+}
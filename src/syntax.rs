@@ -1,87 +1,307 @@
 use ratatui::prelude::*;
-use std::sync::LazyLock;
-use syntect::highlighting::{Theme, ThemeSet};
-use syntect::parsing::SyntaxSet;
+use std::fmt::Debug;
+use std::sync::{Arc, LazyLock};
 
-static SYNTAX_SET: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
-static THEME: LazyLock<Theme> = LazyLock::new(|| {
-    let ts = ThemeSet::load_defaults();
-    ts.themes["base16-eighties.dark"].clone()
-});
+use crate::code_info::CodeInfo;
+use crate::color::ColorDepth;
+use crate::theme::Theme as MarkdownTheme;
 
-const CODE_BG: Color = Color::Rgb(30, 30, 30);
+mod detect;
+#[cfg(feature = "syntect")]
+mod syntect_highlighter;
+#[cfg(feature = "tree-sitter")]
+mod tree_sitter_highlighter;
 
-/// Highlight a code block with syntax coloring.
+pub use detect::guess_language;
+#[cfg(feature = "syntect")]
+pub use syntect_highlighter::{SyntaxTheme, Syntaxes, SyntectHighlighter};
+#[cfg(feature = "tree-sitter")]
+pub use tree_sitter_highlighter::TreeSitterHighlighter;
+
+static DEFAULT_HIGHLIGHTER: LazyLock<Arc<dyn Highlighter>> = LazyLock::new(default_highlighter);
+
+/// A syntax highlighting backend used by [`highlight_code`].
 ///
-/// Returns styled lines with a dark background. If the language is not
-/// recognized, falls back to plain dimmed monospace text.
-pub fn highlight_code(code: &str, language: &str) -> Vec<Line<'static>> {
-    let syntax = SYNTAX_SET
-        .find_syntax_by_token(language)
-        .or_else(|| SYNTAX_SET.find_syntax_by_extension(language));
+/// Implementations only color tokens; the gutter, line numbers, block
+/// background, plain text fallback and color depth are handled by
+/// [`highlight_code`].
+pub trait Highlighter: Debug + Send + Sync {
+    /// Highlight `code` written in `language`, returning one line per source
+    /// line, or `None` if the language is not supported.
+    fn highlight(&self, code: &str, language: &str) -> Option<Vec<Line<'static>>>;
 
-    match syntax {
-        Some(syntax) => highlight_with_syntect(code, syntax),
-        None => plain_code_lines(code),
+    /// Background color preferred by the backend's theme.
+    fn background(&self) -> Option<Color> {
+        None
+    }
+
+    /// Text color preferred by the backend's theme, used for code that
+    /// cannot be highlighted.
+    fn foreground(&self) -> Option<Color> {
+        None
+    }
+
+    /// Name the language whose grammar claims `first_line` (a shebang,
+    /// `<?xml`, a modeline, …), if the backend supports first-line matching.
+    fn detect_language(&self, _first_line: &str) -> Option<String> {
+        None
     }
 }
 
-fn highlight_with_syntect(
-    code: &str,
-    syntax: &syntect::parsing::SyntaxReference,
-) -> Vec<Line<'static>> {
-    use syntect::easy::HighlightLines;
+/// A backend that never highlights; every code block is rendered as plain
+/// text. This is the default when built without highlighting features.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlainHighlighter;
+
+impl Highlighter for PlainHighlighter {
+    fn highlight(&self, _code: &str, _language: &str) -> Option<Vec<Line<'static>>> {
+        None
+    }
+}
+
+/// The backend used when none is chosen: syntect if enabled, otherwise
+/// tree-sitter, otherwise none.
+fn default_highlighter() -> Arc<dyn Highlighter> {
+    #[cfg(feature = "syntect")]
+    return Arc::new(SyntectHighlighter::default());
+
+    #[cfg(all(not(feature = "syntect"), feature = "tree-sitter"))]
+    return Arc::new(TreeSitterHighlighter::default());
 
-    let mut h = HighlightLines::new(syntax, &THEME);
-    let mut lines = Vec::new();
+    #[cfg(not(any(feature = "syntect", feature = "tree-sitter")))]
+    return Arc::new(PlainHighlighter);
+}
 
-    for line_text in code.lines() {
-        let regions = h.highlight_line(line_text, &SYNTAX_SET).unwrap();
+/// Where code blocks take their background color from.
+///
+/// The default is [`Theme`](Self::Theme) rather than the highlighter's own
+/// background, so that a markdown theme such as
+/// [`Theme::monochrome`](crate::Theme::monochrome) decides how the whole
+/// document looks, code blocks included. Choose
+/// [`SyntaxTheme`](Self::SyntaxTheme) to draw blocks as the syntax theme's
+/// author intended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CodeBackground {
+    /// The highlighter theme's own background and foreground, as its author
+    /// intended. Opt in with
+    /// [`Renderer::code_background`](crate::Renderer::code_background).
+    SyntaxTheme,
+    /// [`Theme::code_block_bg`](crate::Theme::code_block_bg) from the markdown theme.
+    #[default]
+    Theme,
+    /// No background; the terminal's own background shows through.
+    Transparent,
+}
 
-        let mut spans: Vec<Span<'static>> = Vec::new();
-        spans.push(Span::styled("    ", Style::default().bg(CODE_BG)));
+/// Settings that control how code blocks are highlighted.
+#[derive(Debug, Clone)]
+pub struct CodeOptions {
+    /// Backend that colors the code.
+    pub highlighter: Arc<dyn Highlighter>,
+    /// Where the block background comes from.
+    pub background: CodeBackground,
+    /// Color depth the highlighted output is quantized to. The renderer
+    /// quantizes the whole document to this depth too; set it with
+    /// [`Renderer::color_depth`](crate::Renderer::color_depth).
+    pub color_depth: ColorDepth,
+    /// Number every code block, not only those whose info string asks for it.
+    pub line_numbers: bool,
+}
+
+impl Default for CodeOptions {
+    fn default() -> Self {
+        Self {
+            highlighter: DEFAULT_HIGHLIGHTER.clone(),
+            background: CodeBackground::default(),
+            color_depth: ColorDepth::default(),
+            line_numbers: false,
+        }
+    }
+}
 
-        for (style, text) in regions {
-            let fg = Color::Rgb(style.foreground.r, style.foreground.g, style.foreground.b);
-            spans.push(Span::styled(
-                text.to_string(),
-                Style::default().fg(fg).bg(CODE_BG),
-            ));
+impl CodeOptions {
+    /// The background color of a code block, or `None` when transparent.
+    pub fn block_background(&self, theme: &MarkdownTheme) -> Option<Color> {
+        match self.background {
+            CodeBackground::SyntaxTheme => Some(
+                self.highlighter
+                    .background()
+                    .unwrap_or(theme.code_block_bg),
+            ),
+            CodeBackground::Theme => Some(theme.code_block_bg),
+            CodeBackground::Transparent => None,
         }
+    }
 
-        lines.push(Line::from(spans));
+    /// Style for unhighlighted code text.
+    fn plain_style(&self, theme: &MarkdownTheme) -> Style {
+        let mut style = theme.code_block_text;
+        if self.background == CodeBackground::SyntaxTheme
+            && let Some(fg) = self.highlighter.foreground()
+        {
+            style = style.fg(fg);
+        }
+        with_bg(style, self.block_background(theme))
     }
+}
 
+/// Highlight a code block with syntax coloring.
+///
+/// Returns styled lines colored by `options.highlighter`, honoring its font
+/// styles and background. If the language is not recognized, falls back to
+/// plain monospace text.
+pub fn highlight_code(
+    code: &str,
+    language: &str,
+    theme: &MarkdownTheme,
+    options: &CodeOptions,
+) -> Vec<Line<'static>> {
+    highlight_code_block(code, language, &CodeInfo::default(), theme, options)
+}
+
+/// Like [`highlight_code`], but also honors the line numbering and emphasized
+/// lines requested by the block's info string. `language` is passed
+/// separately because it may have been guessed rather than taken from `info`.
+pub fn highlight_code_block(
+    code: &str,
+    language: &str,
+    info: &CodeInfo,
+    theme: &MarkdownTheme,
+    options: &CodeOptions,
+) -> Vec<Line<'static>> {
+    let mut lines = match options.highlighter.highlight(code, language) {
+        Some(highlighted) => decorate_highlighted(highlighted, theme, options),
+        None => plain_code_lines(code, theme, options),
+    };
+    add_gutter(&mut lines, info, theme, options);
+    options.color_depth.apply(&mut lines);
     lines
 }
 
-fn plain_code_lines(code: &str) -> Vec<Line<'static>> {
-    let style = Style::default().fg(Color::Gray).bg(CODE_BG);
+/// Resolve token backgrounds for highlighter output.
+fn decorate_highlighted(
+    highlighted: Vec<Line<'static>>,
+    theme: &MarkdownTheme,
+    options: &CodeOptions,
+) -> Vec<Line<'static>> {
+    let bg = options.block_background(theme);
+    highlighted
+        .into_iter()
+        .map(|mut line| {
+            for span in &mut line.spans {
+                span.style.bg = match options.background {
+                    CodeBackground::SyntaxTheme => span.style.bg.or(bg),
+                    _ => bg,
+                };
+            }
+            line
+        })
+        .collect()
+}
+
+fn plain_code_lines(
+    code: &str,
+    theme: &MarkdownTheme,
+    options: &CodeOptions,
+) -> Vec<Line<'static>> {
+    let style = options.plain_style(theme);
     code.lines()
-        .map(|line_text| Line::from(Span::styled(format!("    {}", line_text), style)))
+        .map(|line_text| Line::from(Span::styled(line_text.to_string(), style)))
         .collect()
 }
 
+/// Prefix each line with the four-column gutter, or with right-aligned line
+/// numbers, and patch emphasized lines with the theme's highlight style.
+fn add_gutter(
+    lines: &mut [Line<'static>],
+    info: &CodeInfo,
+    theme: &MarkdownTheme,
+    options: &CodeOptions,
+) {
+    let bg = options.block_background(theme);
+    let numbered = options.line_numbers || info.line_numbers();
+    let start = info.start_line().unwrap_or(1);
+    let last = start.saturating_add(lines.len().saturating_sub(1));
+    let width = last.to_string().len().max(3);
+    let emphasized = info.emphasized_lines();
+
+    for (i, line) in lines.iter_mut().enumerate() {
+        let gutter = if numbered {
+            let number = format!("{:>width$} ", start.saturating_add(i));
+            Span::styled(number, with_bg(theme.code_line_number, bg))
+        } else {
+            Span::styled("    ", with_bg(Style::default(), bg))
+        };
+        line.spans.insert(0, gutter);
+
+        if emphasized.iter().any(|range| range.contains(&(i + 1))) {
+            for span in &mut line.spans {
+                span.style = span.style.patch(theme.code_line_highlight);
+            }
+        }
+    }
+}
+
+fn with_bg(style: Style, bg: Option<Color>) -> Style {
+    match bg {
+        Some(bg) => style.bg(bg),
+        None => style,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Helper: highlight with the default themes and options
+    fn highlight(code: &str, language: &str) -> Vec<Line<'static>> {
+        highlight_code(code, language, &MarkdownTheme::default(), &CodeOptions::default())
+    }
+
+    /// A backend that colors every line red on a white background.
+    #[derive(Debug)]
+    struct RedHighlighter;
+
+    impl Highlighter for RedHighlighter {
+        fn highlight(&self, code: &str, _language: &str) -> Option<Vec<Line<'static>>> {
+            let style = Style::default().fg(Color::Red);
+            Some(
+                code.lines()
+                    .map(|l| Line::from(Span::styled(l.to_string(), style)))
+                    .collect(),
+            )
+        }
+
+        fn background(&self) -> Option<Color> {
+            Some(Color::Rgb(255, 255, 255))
+        }
+    }
+
+    fn red_options() -> CodeOptions {
+        CodeOptions {
+            highlighter: Arc::new(RedHighlighter),
+            ..Default::default()
+        }
+    }
+
     #[test]
+    #[cfg(feature = "syntect")]
     fn known_language_returns_highlighted_lines() {
         let code = "let x = 42;";
-        let lines = highlight_code(code, "rust");
+        let lines = highlight(code, "rust");
         assert!(!lines.is_empty());
         // Should have at least one span with non-default foreground (syntax coloring)
-        let has_colored_span = lines[0].spans.iter().any(|s| {
-            matches!(s.style.fg, Some(Color::Rgb(..)))
-        });
+        let has_colored_span = lines[0]
+            .spans
+            .iter()
+            .any(|s| matches!(s.style.fg, Some(Color::Rgb(..))));
         assert!(has_colored_span, "Expected syntax-highlighted spans");
     }
 
     #[test]
     fn unknown_language_returns_plain_lines() {
         let code = "some text here";
-        let lines = highlight_code(code, "notareallanguage");
+        let lines = highlight(code, "notareallanguage");
         assert_eq!(lines.len(), 1);
         let text: String = lines[0].spans.iter().map(|s| s.content.as_ref()).collect();
         assert!(text.contains("some text here"));
@@ -89,15 +309,115 @@ mod tests {
 
     #[test]
     fn empty_code_returns_empty() {
-        let lines = highlight_code("", "rust");
+        let lines = highlight("", "rust");
         // Empty string has no lines when split by lines()
         assert!(lines.is_empty() || lines.len() == 1);
     }
 
+    #[test]
+    fn custom_highlighter_output_gets_gutter() {
+        let lines = highlight_code("a\nb", "x", &MarkdownTheme::default(), &red_options());
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].spans[0].content, "    ");
+        assert_eq!(lines[1].spans[1].style.fg, Some(Color::Red));
+    }
+
+    #[test]
+    fn uses_theme_background_by_default() {
+        let theme = MarkdownTheme::default();
+        let lines = highlight_code("let x = 1;", "x", &theme, &red_options());
+        assert!(lines[0].spans.iter().all(|s| s.style.bg == Some(theme.code_block_bg)));
+    }
+
+    #[test]
+    fn syntax_theme_background_is_opt_in() {
+        let options = CodeOptions {
+            background: CodeBackground::SyntaxTheme,
+            ..red_options()
+        };
+        let lines = highlight_code("let x = 1;", "x", &MarkdownTheme::default(), &options);
+        let white = Some(Color::Rgb(255, 255, 255));
+        assert!(lines[0].spans.iter().all(|s| s.style.bg == white));
+    }
+
+    #[test]
+    fn transparent_background_sets_no_bg() {
+        let theme = MarkdownTheme::default();
+        let highlighters: [Arc<dyn Highlighter>; 2] =
+            [Arc::new(RedHighlighter), Arc::new(PlainHighlighter)];
+        for highlighter in highlighters {
+            let options = CodeOptions {
+                highlighter,
+                background: CodeBackground::Transparent,
+                ..Default::default()
+            };
+            let lines = highlight_code("let x = 1;", "rust", &theme, &options);
+            assert!(lines[0].spans.iter().all(|s| s.style.bg.is_none()));
+        }
+    }
+
+    #[test]
+    fn color_depth_applies_to_highlighted_output() {
+        let options = CodeOptions {
+            color_depth: ColorDepth::Ansi256,
+            ..red_options()
+        };
+        let lines = highlight_code("let x = 1;", "x", &MarkdownTheme::default(), &options);
+        let is_rgb = |c: Option<Color>| matches!(c, Some(Color::Rgb(..)));
+        assert!(lines[0].spans.iter().all(|s| !is_rgb(s.style.fg) && !is_rgb(s.style.bg)));
+    }
+
+    #[test]
+    fn line_numbers_start_at_startline() {
+        let info = CodeInfo::parse("x startline=99");
+        let theme = MarkdownTheme::default();
+        let lines = highlight_code_block("a\nb", "x", &info, &theme, &red_options());
+        assert_eq!(lines[0].spans[0].content, " 99 ");
+        assert_eq!(lines[1].spans[0].content, "100 ");
+        assert_eq!(lines[1].spans[0].style.fg, theme.code_line_number.fg);
+    }
+
+    #[test]
+    fn huge_startline_does_not_overflow() {
+        let info = CodeInfo::parse("x startline=18446744073709551615");
+        let theme = MarkdownTheme::default();
+        let lines = highlight_code_block("a\nb", "x", &info, &theme, &red_options());
+        assert_eq!(lines[0].spans[0].content, format!("{} ", usize::MAX));
+        assert_eq!(lines[1].spans[0].content, format!("{} ", usize::MAX));
+    }
+
+    #[test]
+    fn emphasized_lines_get_highlight_background() {
+        let info = CodeInfo::parse("x {2}");
+        let theme = MarkdownTheme::default();
+        let highlight_bg = theme.code_line_highlight.bg;
+        let highlighters: [Arc<dyn Highlighter>; 2] =
+            [Arc::new(RedHighlighter), Arc::new(PlainHighlighter)];
+        for highlighter in highlighters {
+            let options = CodeOptions {
+                highlighter,
+                ..Default::default()
+            };
+            let lines = highlight_code_block("a\nb\nc", "x", &info, &theme, &options);
+            assert!(lines[1].spans.iter().all(|s| s.style.bg == highlight_bg));
+            assert!(lines[0].spans.iter().all(|s| s.style.bg != highlight_bg));
+        }
+    }
+
+    #[test]
+    fn line_numbers_can_be_forced_for_every_block() {
+        let options = CodeOptions {
+            line_numbers: true,
+            ..red_options()
+        };
+        let lines = highlight_code("a", "x", &MarkdownTheme::default(), &options);
+        assert_eq!(lines[0].spans[0].content, "  1 ");
+    }
+
     #[test]
     fn multiline_code_returns_multiple_lines() {
         let code = "fn main() {\n    println!(\"hello\");\n}";
-        let lines = highlight_code(code, "rust");
+        let lines = highlight(code, "rust");
         assert_eq!(lines.len(), 3);
     }
 }
@@ -0,0 +1,186 @@
+use super::Highlighter;
+
+/// Guess the language of a code block from its content.
+///
+/// Asks the highlighter first (syntect matches shebangs and first lines such
+/// as `<?xml`), then falls back to lightweight heuristics for shebangs, JSON,
+/// diffs, shell sessions and YAML. Returns a name suitable as a fence
+/// language, or `None` if nothing matched.
+pub fn guess_language(code: &str, highlighter: &dyn Highlighter) -> Option<String> {
+    let first_line = code.lines().find(|l| !l.trim().is_empty())?.trim_end();
+
+    if let Some(language) = highlighter.detect_language(first_line) {
+        return Some(language);
+    }
+
+    if let Some(interpreter) = shebang_language(first_line) {
+        return Some(interpreter);
+    }
+
+    let language = if looks_like_json(code) {
+        "json"
+    } else if looks_like_diff(code) {
+        "diff"
+    } else if looks_like_shell_session(first_line) {
+        "console"
+    } else if looks_like_yaml(code) {
+        "yaml"
+    } else {
+        return None;
+    };
+    Some(language.to_string())
+}
+
+/// The interpreter named by a `#!` line, e.g. `python` for
+/// `#!/usr/bin/env python3`.
+fn shebang_language(first_line: &str) -> Option<String> {
+    let command = first_line.strip_prefix("#!")?;
+    let mut words = command.split_whitespace();
+    let mut program = words.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = words.find(|w| !w.starts_with('-'))?;
+    }
+
+    // Strip version suffixes such as `python3.12`.
+    let name = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    let language = match name {
+        "sh" | "bash" | "zsh" | "dash" | "ksh" => "bash",
+        "node" | "deno" | "bun" => "javascript",
+        "" => return None,
+        other => other,
+    };
+    Some(language.to_string())
+}
+
+fn looks_like_json(code: &str) -> bool {
+    let trimmed = code.trim();
+    let object = trimmed.starts_with('{') && trimmed.ends_with('}');
+    let array = trimmed.starts_with('[') && trimmed.ends_with(']');
+    if object {
+        let inner = trimmed[1..trimmed.len() - 1].trim();
+        inner.is_empty()
+            || (inner.starts_with('"') && (inner.contains("\":") || inner.contains("\" :")))
+    } else if array {
+        let inner = trimmed[1..trimmed.len() - 1].trim_start();
+        inner.is_empty()
+            || inner.starts_with(['{', '[', '"', '-'])
+            || inner.starts_with(|c: char| c.is_ascii_digit())
+    } else {
+        false
+    }
+}
+
+fn looks_like_diff(code: &str) -> bool {
+    let mut lines = code.lines().filter(|l| !l.trim().is_empty());
+    match lines.next() {
+        Some(first) if first.starts_with("diff --git ") || first.starts_with("@@ ") => true,
+        Some(first) if first.starts_with("--- ") => {
+            lines.next().is_some_and(|second| second.starts_with("+++ "))
+        }
+        _ => false,
+    }
+}
+
+/// A `$ ` or `% ` prompt followed by something shaped like a command, so
+/// prose such as `% of users` or `$ 5 each` isn't matched.
+fn looks_like_shell_session(first_line: &str) -> bool {
+    let line = first_line.trim_start();
+    let Some(command) = line.strip_prefix("$ ").or_else(|| line.strip_prefix("% ")) else {
+        return false;
+    };
+    let command = command.split_whitespace().next().unwrap_or("");
+    let is_command_char = |c: char| c.is_ascii_alphanumeric() || "-_./~+".contains(c);
+    command.starts_with(|c: char| c.is_ascii_alphabetic() || "./~".contains(c))
+        && command.chars().all(is_command_char)
+        && !matches!(command, "of" | "or" | "and" | "to" | "in" | "off" | "more" | "less")
+}
+
+/// Every line is a `key: value` pair, a list item, a comment or an indented
+/// continuation, with at least two keys so a lone `Note: …` isn't matched.
+fn looks_like_yaml(code: &str) -> bool {
+    let mut keys = 0;
+    for line in code.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed == "---" {
+            continue;
+        }
+        if trimmed.starts_with("- ") || trimmed == "-" {
+            continue;
+        }
+        match trimmed.split_once(':') {
+            Some((key, rest))
+                if !key.is_empty()
+                    && !key.contains(char::is_whitespace)
+                    && (rest.is_empty() || rest.starts_with(' ')) =>
+            {
+                keys += 1;
+            }
+            // Continuation lines of block scalars are indented.
+            _ if line.starts_with(' ') && keys > 0 => {}
+            _ => return false,
+        }
+    }
+    keys >= 2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::PlainHighlighter;
+
+    fn guess(code: &str) -> Option<String> {
+        guess_language(code, &PlainHighlighter)
+    }
+
+    #[test]
+    fn shebangs_name_the_interpreter() {
+        assert_eq!(guess("#!/bin/bash\necho hi").as_deref(), Some("bash"));
+        assert_eq!(guess("#!/usr/bin/env python3\nprint(1)").as_deref(), Some("python"));
+        assert_eq!(guess("#!/usr/bin/env -S node --flag\n").as_deref(), Some("javascript"));
+    }
+
+    #[test]
+    fn detects_json() {
+        assert_eq!(guess("{\n  \"name\": \"x\",\n  \"n\": 1\n}").as_deref(), Some("json"));
+        assert_eq!(guess("[1, 2, 3]").as_deref(), Some("json"));
+        assert_eq!(guess("{ foo(); }"), None);
+    }
+
+    #[test]
+    fn detects_diff() {
+        let diff = "--- a/file.rs\n+++ b/file.rs\n@@ -1 +1 @@\n-old\n+new";
+        assert_eq!(guess(diff).as_deref(), Some("diff"));
+        assert_eq!(guess("diff --git a/x b/x\n").as_deref(), Some("diff"));
+    }
+
+    #[test]
+    fn detects_shell_sessions() {
+        assert_eq!(guess("$ cargo build\n   Compiling…").as_deref(), Some("console"));
+        assert_eq!(guess("% ./configure --prefix=/usr").as_deref(), Some("console"));
+        assert_eq!(guess("% of users saw it"), None);
+        assert_eq!(guess("$ 5 each, $ 20 total"), None);
+        assert_eq!(guess("% "), None);
+    }
+
+    #[test]
+    fn detects_yaml() {
+        let yaml = "name: build\non:\n  push:\n    branches: [main]\nsteps:\n  - run: make";
+        assert_eq!(guess(yaml).as_deref(), Some("yaml"));
+    }
+
+    #[test]
+    fn prose_is_not_guessed() {
+        assert_eq!(guess("hello world"), None);
+        assert_eq!(guess("Error: file not found"), None);
+        assert_eq!(guess("Note: this is a sentence.\nAnd another one."), None);
+        assert_eq!(guess(""), None);
+    }
+
+    #[cfg(feature = "syntect")]
+    #[test]
+    fn syntect_matches_first_lines() {
+        let highlighter = crate::syntax::SyntectHighlighter::default();
+        let xml = "<?xml version=\"1.0\"?>\n<root/>";
+        assert_eq!(guess_language(xml, &highlighter).as_deref(), Some("XML"));
+    }
+}
@@ -0,0 +1,399 @@
+use ratatui::prelude::*;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, LazyLock};
+use syntect::LoadingError;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{FontStyle, Theme, ThemeSet};
+use syntect::parsing::{SyntaxDefinition, SyntaxReference, SyntaxSet};
+use syntect::util::LinesWithEndings;
+
+use super::Highlighter;
+
+static SYNTAX_SET: LazyLock<Arc<SyntaxSet>> = LazyLock::new(|| Arc::new(default_syntax_set()));
+static THEME_SET: LazyLock<ThemeSet> = LazyLock::new(ThemeSet::load_defaults);
+
+/// A syntect color scheme used to highlight code blocks.
+///
+/// Pick one of the themes bundled with syntect by name, or load a `.tmTheme`
+/// file from disk. Cloning is cheap; the underlying theme is shared.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxTheme {
+    theme: Arc<Theme>,
+}
+
+impl SyntaxTheme {
+    /// Name of the bundled theme used when none is chosen.
+    pub const DEFAULT: &'static str = "base16-eighties.dark";
+
+    /// Look up a theme bundled with syntect, e.g. `"InspiredGitHub"` or
+    /// `"Solarized (light)"`. See [`SyntaxTheme::bundled_names`].
+    pub fn named(name: &str) -> Option<Self> {
+        THEME_SET.themes.get(name).map(|t| Self::from_syntect(t.clone()))
+    }
+
+    /// Names of all themes bundled with syntect, sorted alphabetically.
+    pub fn bundled_names() -> Vec<&'static str> {
+        THEME_SET.themes.keys().map(String::as_str).collect()
+    }
+
+    /// Load a `.tmTheme` file from disk.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, LoadingError> {
+        ThemeSet::get_theme(path).map(Self::from_syntect)
+    }
+
+    /// Wrap an already-loaded syntect theme.
+    pub fn from_syntect(theme: Theme) -> Self {
+        Self {
+            theme: Arc::new(theme),
+        }
+    }
+
+    /// The theme's name, if its file declares one.
+    pub fn name(&self) -> Option<&str> {
+        self.theme.name.as_deref()
+    }
+
+    fn as_syntect(&self) -> &Theme {
+        &self.theme
+    }
+}
+
+impl Default for SyntaxTheme {
+    fn default() -> Self {
+        Self::named(Self::DEFAULT).expect("default syntect theme is bundled")
+    }
+}
+
+/// The bundled grammars: syntect's defaults, or bat's extended pack when the
+/// `extra-syntaxes` feature is enabled.
+#[cfg(feature = "extra-syntaxes")]
+fn default_syntax_set() -> SyntaxSet {
+    two_face::syntax::extra_newlines()
+}
+
+#[cfg(not(feature = "extra-syntaxes"))]
+fn default_syntax_set() -> SyntaxSet {
+    SyntaxSet::load_defaults_newlines()
+}
+
+/// Language aliases understood out of the box, mapped to syntax names.
+///
+/// Targets that are missing from the syntax set are skipped, so aliases for
+/// languages only available through custom definitions are harmless.
+const DEFAULT_ALIASES: &[(&str, &str)] = &[
+    ("sh", "Bourne Again Shell (bash)"),
+    ("shell", "Bourne Again Shell (bash)"),
+    ("console", "Bourne Again Shell (bash)"),
+    ("shell-session", "Bourne Again Shell (bash)"),
+    ("zsh", "Bourne Again Shell (bash)"),
+    ("jsonc", "JSON"),
+    ("json5", "JSON"),
+    ("yml", "YAML"),
+    ("js", "JavaScript"),
+    ("jsx", "JavaScript"),
+    ("mjs", "JavaScript"),
+    ("cjs", "JavaScript"),
+    ("ts", "TypeScript"),
+    ("typescript", "TypeScript"),
+    ("tsx", "TypeScriptReact"),
+    ("py", "Python"),
+    ("python3", "Python"),
+    ("rs", "Rust"),
+    ("golang", "Go"),
+    ("dockerfile", "Dockerfile"),
+    ("docker", "Dockerfile"),
+    ("tf", "Terraform"),
+    ("hcl", "Terraform"),
+    ("proto", "Protocol Buffer"),
+    ("kt", "Kotlin"),
+];
+
+/// Syntax definitions and language aliases used to find a grammar for a
+/// code block's language.
+///
+/// Starts out with syntect's bundled grammars. Add `.sublime-syntax`
+/// definitions from a directory or from embedded source, and map extra
+/// language names with [`Syntaxes::alias`]. Cloning is cheap; the compiled
+/// syntax set is shared.
+#[derive(Debug, Clone)]
+pub struct Syntaxes {
+    set: Arc<SyntaxSet>,
+    aliases: HashMap<String, String>,
+}
+
+impl Syntaxes {
+    /// Add every `.sublime-syntax` file found (recursively) in `folder`.
+    pub fn with_folder(self, folder: impl AsRef<Path>) -> Result<Self, LoadingError> {
+        let mut builder = (*self.set).clone().into_builder();
+        builder.add_from_folder(folder, true)?;
+        Ok(Self {
+            set: Arc::new(builder.build()),
+            ..self
+        })
+    }
+
+    /// Add a single `.sublime-syntax` definition from its YAML source, for
+    /// example one embedded with `include_str!`.
+    pub fn with_definition(self, source: &str) -> Result<Self, LoadingError> {
+        let definition = SyntaxDefinition::load_from_str(source, true, None)
+            .map_err(|e| LoadingError::ParseSyntax(e, String::from("<embedded>")))?;
+        let mut builder = (*self.set).clone().into_builder();
+        builder.add(definition);
+        Ok(Self {
+            set: Arc::new(builder.build()),
+            ..self
+        })
+    }
+
+    /// Treat `alias` (case-insensitive) as `target`, which may be a syntax
+    /// name such as `"TypeScript"`, a token, or a file extension.
+    pub fn alias(mut self, alias: &str, target: &str) -> Self {
+        self.aliases
+            .insert(alias.to_ascii_lowercase(), target.to_string());
+        self
+    }
+
+    /// Names of all loaded syntaxes.
+    pub fn names(&self) -> Vec<&str> {
+        self.set.syntaxes().iter().map(|s| s.name.as_str()).collect()
+    }
+
+    /// Find the grammar for a code block language, resolving aliases first.
+    fn find(&self, language: &str) -> Option<&SyntaxReference> {
+        if language.is_empty() {
+            return None;
+        }
+        self.aliases
+            .get(&language.to_ascii_lowercase())
+            .and_then(|target| self.lookup(target))
+            .or_else(|| self.lookup(language))
+    }
+
+    fn lookup(&self, name: &str) -> Option<&SyntaxReference> {
+        self.set
+            .find_syntax_by_name(name)
+            .or_else(|| self.set.find_syntax_by_token(name))
+            .or_else(|| self.set.find_syntax_by_extension(name))
+    }
+}
+
+impl Default for Syntaxes {
+    fn default() -> Self {
+        Self {
+            set: SYNTAX_SET.clone(),
+            aliases: DEFAULT_ALIASES
+                .iter()
+                .map(|(alias, target)| (alias.to_string(), target.to_string()))
+                .collect(),
+        }
+    }
+}
+
+/// The [`syntect`](https://docs.rs/syntect) highlighting backend, using
+/// Sublime Text grammars and TextMate themes.
+#[derive(Debug, Clone, Default)]
+pub struct SyntectHighlighter {
+    syntaxes: Syntaxes,
+    theme: SyntaxTheme,
+}
+
+impl SyntectHighlighter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Color code with `theme` instead of [`SyntaxTheme::default()`].
+    pub fn theme(mut self, theme: SyntaxTheme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Look up languages in `syntaxes` instead of the bundled defaults.
+    pub fn syntaxes(mut self, syntaxes: Syntaxes) -> Self {
+        self.syntaxes = syntaxes;
+        self
+    }
+}
+
+impl Highlighter for SyntectHighlighter {
+    fn highlight(&self, code: &str, language: &str) -> Option<Vec<Line<'static>>> {
+        let syntax = self.syntaxes.find(language)?;
+        let mut h = HighlightLines::new(syntax, self.theme.as_syntect());
+        let mut lines = Vec::new();
+
+        // The grammars expect lines that include their trailing newline.
+        for line_text in LinesWithEndings::from(code) {
+            let regions = h
+                .highlight_line(line_text, &self.syntaxes.set)
+                .unwrap_or_default();
+
+            let mut spans: Vec<Span<'static>> = Vec::new();
+            for (style, text) in regions {
+                let text = text.trim_end_matches(['\n', '\r']);
+                if text.is_empty() {
+                    continue;
+                }
+                let span_style = Style::default()
+                    .fg(to_color(style.foreground))
+                    .bg(to_color(style.background))
+                    .add_modifier(to_modifier(style.font_style));
+                spans.push(Span::styled(text.to_string(), span_style));
+            }
+
+            lines.push(Line::from(spans));
+        }
+
+        Some(lines)
+    }
+
+    fn background(&self) -> Option<Color> {
+        self.theme.as_syntect().settings.background.map(to_color)
+    }
+
+    fn foreground(&self) -> Option<Color> {
+        self.theme.as_syntect().settings.foreground.map(to_color)
+    }
+
+    fn detect_language(&self, first_line: &str) -> Option<String> {
+        self.syntaxes
+            .set
+            .find_syntax_by_first_line(first_line)
+            .map(|syntax| syntax.name.clone())
+    }
+}
+
+fn to_color(c: syntect::highlighting::Color) -> Color {
+    Color::Rgb(c.r, c.g, c.b)
+}
+
+fn to_modifier(font_style: FontStyle) -> Modifier {
+    let mut modifier = Modifier::empty();
+    if font_style.contains(FontStyle::BOLD) {
+        modifier |= Modifier::BOLD;
+    }
+    if font_style.contains(FontStyle::ITALIC) {
+        modifier |= Modifier::ITALIC;
+    }
+    if font_style.contains(FontStyle::UNDERLINE) {
+        modifier |= Modifier::UNDERLINED;
+    }
+    modifier
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Helper: highlight with the default syntect backend
+    fn highlight(code: &str, language: &str) -> Vec<Line<'static>> {
+        SyntectHighlighter::default()
+            .highlight(code, language)
+            .expect("language is known")
+    }
+
+    #[test]
+    fn unknown_language_is_not_highlighted() {
+        assert!(SyntectHighlighter::default().highlight("x", "notareallanguage").is_none());
+        assert!(SyntectHighlighter::default().highlight("x", "").is_none());
+    }
+
+    #[test]
+    fn bundled_themes_are_selectable_by_name() {
+        let names = SyntaxTheme::bundled_names();
+        assert!(names.contains(&SyntaxTheme::DEFAULT));
+        assert!(names.contains(&"InspiredGitHub"));
+        assert!(SyntaxTheme::named("InspiredGitHub").is_some());
+        assert!(SyntaxTheme::named("no-such-theme").is_none());
+    }
+
+    #[test]
+    fn different_themes_produce_different_colors() {
+        let code = "fn main() {}";
+        let dark = highlight(code, "rust");
+        let light = SyntectHighlighter::new()
+            .theme(SyntaxTheme::named("InspiredGitHub").unwrap())
+            .highlight(code, "rust")
+            .unwrap();
+        let colors = |lines: &[Line<'_>]| -> Vec<Option<Color>> {
+            lines[0].spans.iter().map(|s| s.style.fg).collect()
+        };
+        assert_ne!(colors(&dark), colors(&light));
+    }
+
+    #[test]
+    fn theme_background_is_reported() {
+        let highlighter =
+            SyntectHighlighter::new().theme(SyntaxTheme::named("InspiredGitHub").unwrap());
+        assert_eq!(highlighter.background(), Some(Color::Rgb(255, 255, 255)));
+    }
+
+    #[test]
+    fn font_styles_map_to_modifiers() {
+        assert_eq!(
+            to_modifier(FontStyle::BOLD | FontStyle::ITALIC),
+            Modifier::BOLD | Modifier::ITALIC
+        );
+        assert_eq!(to_modifier(FontStyle::UNDERLINE), Modifier::UNDERLINED);
+        assert_eq!(to_modifier(FontStyle::empty()), Modifier::empty());
+    }
+
+    #[test]
+    fn aliases_resolve_to_bundled_syntaxes() {
+        let syntaxes = Syntaxes::default();
+        assert_eq!(syntaxes.find("sh").unwrap().name, "Bourne Again Shell (bash)");
+        assert_eq!(syntaxes.find("JSONC").unwrap().name, "JSON");
+        assert_eq!(syntaxes.find("rust").unwrap().name, "Rust");
+        assert!(syntaxes.find("").is_none());
+    }
+
+    #[test]
+    fn custom_alias_and_definition_are_used() {
+        let source = r"%YAML 1.2
+---
+name: Toy
+file_extensions: [toy]
+scope: source.toy
+contexts:
+  main:
+    - match: '\bboop\b'
+      scope: keyword.control.toy
+";
+        let syntaxes = Syntaxes::default()
+            .with_definition(source)
+            .unwrap()
+            .alias("toylang", "Toy");
+        assert!(syntaxes.names().contains(&"Toy"));
+        assert_eq!(syntaxes.find("toylang").unwrap().name, "Toy");
+
+        let lines = SyntectHighlighter::new()
+            .syntaxes(syntaxes)
+            .highlight("boop", "toy")
+            .unwrap();
+        let text: String = lines[0].spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(text, "boop");
+    }
+
+    #[cfg(feature = "extra-syntaxes")]
+    #[test]
+    fn extra_syntaxes_are_bundled() {
+        let syntaxes = Syntaxes::default();
+        let langs = [
+            "toml", "ts", "tsx", "dockerfile", "hcl", "proto", "graphql", "kotlin", "swift", "zig",
+            "nix",
+        ];
+        for lang in langs {
+            assert!(syntaxes.find(lang).is_some(), "missing syntax for {lang}");
+        }
+    }
+
+    #[test]
+    fn invalid_definition_is_an_error() {
+        assert!(Syntaxes::default().with_definition("not: [valid").is_err());
+    }
+
+    #[test]
+    fn missing_theme_file_is_an_error() {
+        assert!(SyntaxTheme::from_file("/nonexistent/theme.tmTheme").is_err());
+    }
+}
@@ -0,0 +1,266 @@
+use ratatui::prelude::*;
+use std::sync::LazyLock;
+use tree_sitter_highlight::{HighlightConfiguration, HighlightEvent};
+
+use super::Highlighter;
+
+/// Capture names recognized in highlight queries. Captures such as
+/// `keyword.control` fall back to their longest listed prefix.
+const CAPTURE_NAMES: &[&str] = &[
+    "attribute",
+    "comment",
+    "constant",
+    "constant.builtin",
+    "constructor",
+    "embedded",
+    "escape",
+    "function",
+    "function.builtin",
+    "function.macro",
+    "function.method",
+    "keyword",
+    "label",
+    "module",
+    "number",
+    "operator",
+    "property",
+    "punctuation",
+    "punctuation.bracket",
+    "punctuation.delimiter",
+    "punctuation.special",
+    "string",
+    "string.escape",
+    "string.special",
+    "tag",
+    "type",
+    "type.builtin",
+    "variable",
+    "variable.builtin",
+    "variable.parameter",
+];
+
+/// A grammar with its highlight queries and the fence names that select it.
+struct Language {
+    names: &'static [&'static str],
+    config: HighlightConfiguration,
+}
+
+static LANGUAGES: LazyLock<Vec<Language>> = LazyLock::new(|| {
+    let js_highlights = tree_sitter_javascript::HIGHLIGHT_QUERY;
+    let jsx_highlights = tree_sitter_javascript::JSX_HIGHLIGHT_QUERY;
+    let ts_highlights = tree_sitter_typescript::HIGHLIGHTS_QUERY;
+    let js_locals = tree_sitter_javascript::LOCALS_QUERY;
+    let ts_locals = tree_sitter_typescript::LOCALS_QUERY;
+
+    let specs: Vec<(&'static [&'static str], tree_sitter::Language, String, String)> = vec![
+        (
+            &["rust", "rs"],
+            tree_sitter_rust::LANGUAGE.into(),
+            tree_sitter_rust::HIGHLIGHTS_QUERY.to_string(),
+            String::new(),
+        ),
+        (
+            &["javascript", "js", "jsx", "mjs", "cjs"],
+            tree_sitter_javascript::LANGUAGE.into(),
+            format!("{jsx_highlights}\n{js_highlights}"),
+            js_locals.to_string(),
+        ),
+        (
+            &["typescript", "ts", "mts", "cts"],
+            tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            format!("{ts_highlights}\n{js_highlights}"),
+            format!("{ts_locals}\n{js_locals}"),
+        ),
+        (
+            &["tsx"],
+            tree_sitter_typescript::LANGUAGE_TSX.into(),
+            format!("{jsx_highlights}\n{ts_highlights}\n{js_highlights}"),
+            format!("{ts_locals}\n{js_locals}"),
+        ),
+        (
+            &["python", "py", "python3"],
+            tree_sitter_python::LANGUAGE.into(),
+            tree_sitter_python::HIGHLIGHTS_QUERY.to_string(),
+            String::new(),
+        ),
+    ];
+
+    specs
+        .into_iter()
+        .filter_map(|(names, language, highlights, locals)| {
+            let mut config =
+                HighlightConfiguration::new(language, names[0], &highlights, "", &locals).ok()?;
+            config.configure(CAPTURE_NAMES);
+            Some(Language { names, config })
+        })
+        .collect()
+});
+
+/// A highlighting backend built on tree-sitter grammars and highlight
+/// queries. Parses code into a real syntax tree, which gives more accurate
+/// results than regex grammars for Rust, JavaScript, TypeScript/TSX and
+/// Python.
+#[derive(Debug, Clone)]
+pub struct TreeSitterHighlighter {
+    styles: Vec<Style>,
+}
+
+impl TreeSitterHighlighter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Capture names that can be styled with [`TreeSitterHighlighter::style`],
+    /// such as `"keyword"` or `"string.escape"`.
+    pub fn capture_names() -> &'static [&'static str] {
+        CAPTURE_NAMES
+    }
+
+    /// Style tokens captured as `capture`. Unknown capture names are ignored.
+    pub fn style(mut self, capture: &str, style: Style) -> Self {
+        if let Some(i) = CAPTURE_NAMES.iter().position(|name| *name == capture) {
+            self.styles[i] = style;
+        }
+        self
+    }
+
+    /// Whether a grammar is available for `language`.
+    pub fn supports(language: &str) -> bool {
+        find_language(language).is_some()
+    }
+}
+
+impl Default for TreeSitterHighlighter {
+    /// A palette of the 16 basic colors, readable on dark terminals.
+    fn default() -> Self {
+        let fg = |c: Color| Style::default().fg(c);
+        let styles = CAPTURE_NAMES
+            .iter()
+            .map(|name| match *name {
+                "comment" => fg(Color::DarkGray).add_modifier(Modifier::ITALIC),
+                "keyword" => fg(Color::Magenta),
+                "string" | "string.special" => fg(Color::Green),
+                "escape" | "string.escape" => fg(Color::Cyan),
+                "number" | "constant" | "constant.builtin" => fg(Color::Yellow),
+                "function" | "function.builtin" | "function.method" => fg(Color::Blue),
+                "function.macro" => fg(Color::LightBlue),
+                "type" | "type.builtin" | "constructor" | "module" => fg(Color::LightYellow),
+                "attribute" | "label" => fg(Color::LightCyan),
+                "property" => fg(Color::Cyan),
+                "tag" => fg(Color::Red),
+                "variable.builtin" | "variable.parameter" => fg(Color::LightRed),
+                "operator" | "punctuation" | "punctuation.bracket" | "punctuation.delimiter"
+                | "punctuation.special" => fg(Color::Gray),
+                _ => Style::default(),
+            })
+            .collect();
+        Self { styles }
+    }
+}
+
+fn find_language(language: &str) -> Option<&'static Language> {
+    let language = language.to_ascii_lowercase();
+    LANGUAGES
+        .iter()
+        .find(|l| l.names.contains(&language.as_str()))
+}
+
+impl Highlighter for TreeSitterHighlighter {
+    fn highlight(&self, code: &str, language: &str) -> Option<Vec<Line<'static>>> {
+        let language = find_language(language)?;
+        if code.is_empty() {
+            return Some(Vec::new());
+        }
+
+        let mut highlighter = tree_sitter_highlight::Highlighter::new();
+        let events = highlighter
+            .highlight(&language.config, code.as_bytes(), None, None, |_| None)
+            .ok()?;
+
+        let mut lines = vec![Line::default()];
+        let mut style_stack = vec![Style::default()];
+
+        for event in events {
+            match event.ok()? {
+                HighlightEvent::HighlightStart(h) => {
+                    let base = *style_stack.last().unwrap_or(&Style::default());
+                    style_stack.push(base.patch(self.styles[h.0]));
+                }
+                HighlightEvent::HighlightEnd => {
+                    style_stack.pop();
+                }
+                HighlightEvent::Source { start, end } => {
+                    let style = *style_stack.last().unwrap_or(&Style::default());
+                    for (i, part) in code[start..end].split('\n').enumerate() {
+                        if i > 0 {
+                            lines.push(Line::default());
+                        }
+                        let part = part.trim_end_matches('\r');
+                        if !part.is_empty()
+                            && let Some(line) = lines.last_mut()
+                        {
+                            line.spans.push(Span::styled(part.to_string(), style));
+                        }
+                    }
+                }
+            }
+        }
+
+        // Like `str::lines`, a trailing newline does not start another line.
+        if code.ends_with('\n') {
+            lines.pop();
+        }
+
+        Some(lines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line_text(line: &Line<'_>) -> String {
+        line.spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn highlights_supported_languages() {
+        let highlighter = TreeSitterHighlighter::default();
+        for (lang, code) in [
+            ("rust", "fn main() { let x = 1; }"),
+            ("ts", "const x: number = 1;"),
+            ("tsx", "const el = <div>hi</div>;"),
+            ("js", "function f() { return 1; }"),
+            ("python", "def f():\n    return 1"),
+        ] {
+            let lines = highlighter.highlight(code, lang).expect(lang);
+            assert!(
+                lines.iter().flat_map(|l| &l.spans).any(|s| s.style.fg.is_some()),
+                "no highlighting for {lang}"
+            );
+        }
+    }
+
+    #[test]
+    fn preserves_text_and_line_breaks() {
+        let code = "fn main() {\n    println!(\"hi\");\n}\n";
+        let lines = TreeSitterHighlighter::default().highlight(code, "rust").unwrap();
+        let texts: Vec<String> = lines.iter().map(line_text).collect();
+        assert_eq!(texts, vec!["fn main() {", "    println!(\"hi\");", "}"]);
+    }
+
+    #[test]
+    fn keyword_style_is_configurable() {
+        let highlighter =
+            TreeSitterHighlighter::new().style("keyword", Style::default().fg(Color::Red));
+        let lines = highlighter.highlight("fn main() {}", "rust").unwrap();
+        assert_eq!(lines[0].spans[0].content, "fn");
+        assert_eq!(lines[0].spans[0].style.fg, Some(Color::Red));
+    }
+
+    #[test]
+    fn unsupported_language_is_not_highlighted() {
+        assert!(!TreeSitterHighlighter::supports("cobol"));
+        assert!(TreeSitterHighlighter::default().highlight("x", "cobol").is_none());
+    }
+}
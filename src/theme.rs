@@ -0,0 +1,248 @@
+use ratatui::prelude::*;
+
+/// Styles applied by the renderer to each markdown element.
+///
+/// Every color the renderer emits comes from a `Theme`, so an application can
+/// match its own palette. Use [`Theme::default()`], [`Theme::light()`] or
+/// [`Theme::monochrome()`] as a starting point and override individual fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    /// Heading styles, indexed by level (`headings[0]` is H1).
+    pub headings: [Style; 6],
+    /// Link text.
+    pub link: Style,
+    /// The ` (url)` suffix or `[n]` reference number printed after links,
+    /// and the destinations listed at the end by
+    /// [`LinkDisplay::Numbered`](crate::LinkDisplay::Numbered).
+    pub link_url: Style,
+    /// Patched over the link focused with Tab/Shift-Tab.
+    pub link_focused: Style,
+    /// Footnote reference markers and the numbers of the notes.
+    pub footnote: Style,
+    /// Image alt text markers (`[img: …]`).
+    pub image: Style,
+    /// Inline `code` spans.
+    pub inline_code: Style,
+    /// Math converted to Unicode, inline or on its own lines.
+    pub math: Style,
+    /// TeX outside the supported subset, shown as written.
+    pub math_raw: Style,
+    /// Background of fenced and indented code blocks.
+    pub code_block_bg: Color,
+    /// Code block text when no syntax highlighting is available.
+    pub code_block_text: Style,
+    /// Language label shown above a code block.
+    pub code_block_label: Style,
+    /// Line numbers in the code block gutter.
+    pub code_line_number: Style,
+    /// Patched over emphasized code lines, e.g. `{3,7-9}`. Its background
+    /// replaces the block and token backgrounds on those lines.
+    pub code_line_highlight: Style,
+    /// Table box-drawing borders.
+    pub table_border: Style,
+    /// Table header cells.
+    pub table_header: Style,
+    /// The `▌` bar in front of blockquotes.
+    pub blockquote_bar: Style,
+    /// Unstyled text inside blockquotes.
+    pub blockquote_text: Style,
+    /// Bar, icon and title of `> [!NOTE]` alerts.
+    pub alert_note: Style,
+    /// Bar, icon and title of `> [!TIP]` alerts.
+    pub alert_tip: Style,
+    /// Bar, icon and title of `> [!IMPORTANT]` alerts.
+    pub alert_important: Style,
+    /// Bar, icon and title of `> [!WARNING]` alerts.
+    pub alert_warning: Style,
+    /// Bar, icon and title of `> [!CAUTION]` alerts.
+    pub alert_caution: Style,
+    /// Bullets and numbers of list items.
+    pub list_marker: Style,
+    /// Checked task list marker (`☑`).
+    pub task_checked: Style,
+    /// Unchecked task list marker (`☐`).
+    pub task_unchecked: Style,
+    /// Horizontal rules.
+    pub rule: Style,
+    /// Byline and tags of the front matter card.
+    pub front_matter: Style,
+    /// The section in view, in a [`TableOfContents`](crate::TableOfContents).
+    pub toc_active: Style,
+    /// Search matches.
+    pub search_match: Style,
+    /// The search match selected with next/previous.
+    pub search_current: Style,
+}
+
+impl Theme {
+    /// Style for a heading of the given level (1–6). Out-of-range levels are
+    /// clamped.
+    pub fn heading(&self, level: usize) -> Style {
+        self.headings[level.clamp(1, 6) - 1]
+    }
+
+    /// A palette for terminals with a light background.
+    pub fn light() -> Self {
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        let gray = Style::default().fg(Color::Gray);
+        Self {
+            headings: [
+                bold.fg(Color::Blue),
+                bold.fg(Color::Magenta),
+                bold.fg(Color::Red),
+                bold.fg(Color::Red),
+                bold.fg(Color::Red),
+                bold.fg(Color::Red),
+            ],
+            link: Style::default()
+                .fg(Color::Blue)
+                .add_modifier(Modifier::UNDERLINED),
+            link_url: gray,
+            link_focused: Style::default().add_modifier(Modifier::REVERSED),
+            footnote: Style::default().fg(Color::Blue),
+            image: gray,
+            inline_code: Style::default().fg(Color::Red),
+            math: Style::default().fg(Color::Magenta),
+            math_raw: gray,
+            code_block_bg: Color::Rgb(240, 240, 240),
+            code_block_text: Style::default().fg(Color::Black),
+            code_block_label: gray,
+            code_line_number: gray,
+            code_line_highlight: Style::default().bg(Color::Rgb(255, 245, 200)),
+            table_border: gray,
+            table_header: bold.fg(Color::Blue),
+            blockquote_bar: gray,
+            blockquote_text: Style::default().add_modifier(Modifier::ITALIC),
+            alert_note: bold.fg(Color::Blue),
+            alert_tip: bold.fg(Color::Green),
+            alert_important: bold.fg(Color::Magenta),
+            alert_warning: bold.fg(Color::Rgb(154, 103, 0)),
+            alert_caution: bold.fg(Color::Red),
+            list_marker: Style::default().fg(Color::Blue),
+            task_checked: Style::default().fg(Color::Green),
+            task_unchecked: Style::default().fg(Color::Red),
+            rule: gray,
+            front_matter: gray,
+            toc_active: bold.fg(Color::Blue),
+            search_match: Style::default().bg(Color::LightYellow),
+            search_current: Style::default().fg(Color::Black).bg(Color::Yellow),
+        }
+    }
+
+    /// A palette that uses no colors at all, only text modifiers.
+    pub fn monochrome() -> Self {
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        let dim = Style::default().add_modifier(Modifier::DIM);
+        Self {
+            headings: [
+                bold.add_modifier(Modifier::UNDERLINED),
+                bold,
+                bold,
+                bold,
+                bold,
+                bold,
+            ],
+            link: Style::default().add_modifier(Modifier::UNDERLINED),
+            link_url: dim,
+            link_focused: Style::default().add_modifier(Modifier::REVERSED),
+            footnote: bold,
+            image: dim,
+            inline_code: Style::default().add_modifier(Modifier::REVERSED),
+            math: Style::default().add_modifier(Modifier::ITALIC),
+            math_raw: dim,
+            code_block_bg: Color::Reset,
+            code_block_text: Style::default(),
+            code_block_label: dim,
+            code_line_number: dim,
+            code_line_highlight: Style::default().add_modifier(Modifier::REVERSED),
+            table_border: dim,
+            table_header: bold,
+            blockquote_bar: dim,
+            blockquote_text: Style::default().add_modifier(Modifier::ITALIC),
+            alert_note: bold,
+            alert_tip: bold,
+            alert_important: bold,
+            alert_warning: bold,
+            alert_caution: bold,
+            list_marker: bold,
+            task_checked: bold,
+            task_unchecked: Style::default(),
+            rule: dim,
+            front_matter: dim,
+            toc_active: bold,
+            search_match: Style::default().add_modifier(Modifier::UNDERLINED),
+            search_current: Style::default().add_modifier(Modifier::REVERSED),
+        }
+    }
+}
+
+impl Default for Theme {
+    /// The original tui-md palette, designed for dark terminals.
+    fn default() -> Self {
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        let dark_gray = Style::default().fg(Color::DarkGray);
+        Self {
+            headings: [
+                bold.fg(Color::Cyan),
+                bold.fg(Color::Green),
+                bold.fg(Color::Yellow),
+                bold.fg(Color::Yellow),
+                bold.fg(Color::Yellow),
+                bold.fg(Color::Yellow),
+            ],
+            link: Style::default()
+                .fg(Color::Blue)
+                .add_modifier(Modifier::UNDERLINED),
+            link_url: dark_gray,
+            link_focused: Style::default().add_modifier(Modifier::REVERSED),
+            footnote: Style::default().fg(Color::Cyan),
+            image: dark_gray,
+            inline_code: Style::default().fg(Color::Magenta),
+            math: Style::default().fg(Color::LightCyan),
+            math_raw: dark_gray,
+            code_block_bg: Color::Rgb(30, 30, 30),
+            code_block_text: Style::default().fg(Color::Gray),
+            code_block_label: dark_gray,
+            code_line_number: dark_gray,
+            code_line_highlight: Style::default().bg(Color::Rgb(60, 56, 30)),
+            table_border: dark_gray,
+            table_header: bold.fg(Color::Cyan),
+            blockquote_bar: dark_gray,
+            blockquote_text: Style::default().add_modifier(Modifier::ITALIC),
+            alert_note: bold.fg(Color::LightBlue),
+            alert_tip: bold.fg(Color::LightGreen),
+            alert_important: bold.fg(Color::LightMagenta),
+            alert_warning: bold.fg(Color::Yellow),
+            alert_caution: bold.fg(Color::LightRed),
+            list_marker: Style::default().fg(Color::Cyan),
+            task_checked: Style::default().fg(Color::Green),
+            task_unchecked: Style::default().fg(Color::Yellow),
+            rule: dark_gray,
+            front_matter: dark_gray,
+            toc_active: bold.fg(Color::Cyan),
+            search_match: Style::default().fg(Color::Black).bg(Color::Yellow),
+            search_current: Style::default().fg(Color::Black).bg(Color::LightRed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn heading_level_is_clamped() {
+        let theme = Theme::default();
+        assert_eq!(theme.heading(0), theme.headings[0]);
+        assert_eq!(theme.heading(9), theme.headings[5]);
+    }
+
+    #[test]
+    fn monochrome_has_no_foreground_colors() {
+        let theme = Theme::monochrome();
+        assert!(theme.headings.iter().all(|s| s.fg.is_none()));
+        assert!(theme.link.fg.is_none());
+        assert!(theme.inline_code.fg.is_none());
+        assert!(theme.table_border.fg.is_none());
+    }
+}
@@ -0,0 +1,276 @@
+use ratatui::prelude::*;
+use ratatui::widgets::{Scrollbar, ScrollbarOrientation, ScrollbarState};
+
+use crate::links::{self, LinkDisplay};
+use crate::search::SearchState;
+use crate::{Renderer, Theme};
+
+/// A scrollable markdown viewer.
+///
+/// Lays the document out at the width of the area it is drawn into and shows
+/// the lines starting at [`MarkdownViewState::offset`]. Rendering records the
+/// content and viewport heights in the state, so later scrolling is clamped
+/// to the document.
+///
+/// ```no_run
+/// # use ratatui::prelude::*;
+/// # use tui_md::{MarkdownView, MarkdownViewState};
+/// # fn draw(frame: &mut Frame, state: &mut MarkdownViewState) {
+/// let view = MarkdownView::new("# Title\n\nSome *markdown*.").scrollbar(true);
+/// frame.render_stateful_widget(view, frame.area(), state);
+/// # }
+/// ```
+pub struct MarkdownView {
+    input: String,
+    renderer: Renderer,
+    scrollbar: bool,
+    search: Option<SearchState>,
+}
+
+impl MarkdownView {
+    pub fn new(input: &str) -> Self {
+        Self::with_renderer(input, Renderer::new())
+    }
+
+    /// Like [`MarkdownView::new`], but styled with a custom [`Theme`].
+    pub fn with_theme(input: &str, theme: &Theme) -> Self {
+        Self::with_renderer(input, Renderer::new().theme(theme.clone()))
+    }
+
+    /// Render `input` with a fully configured [`Renderer`].
+    pub fn with_renderer(input: &str, renderer: Renderer) -> Self {
+        Self {
+            input: input.to_string(),
+            renderer,
+            scrollbar: false,
+            search: None,
+        }
+    }
+
+    /// Show a vertical scrollbar in the rightmost column when the document is
+    /// taller than the area.
+    pub fn scrollbar(mut self, enabled: bool) -> Self {
+        self.scrollbar = enabled;
+        self
+    }
+
+    /// Highlight the matches of `search` in the theme's search styles.
+    pub fn search(mut self, search: &SearchState) -> Self {
+        self.search = Some(search.clone());
+        self
+    }
+}
+
+/// Scroll position of a [`MarkdownView`].
+///
+/// The content and viewport heights are those seen by the most recent
+/// render; until the first render they are zero and every offset clamps to
+/// the top.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MarkdownViewState {
+    offset: usize,
+    content_height: usize,
+    viewport_height: usize,
+    // Where the text was last drawn, for hit-testing
+    pub(crate) area: Rect,
+    pub(crate) focused_link: Option<usize>,
+}
+
+impl MarkdownViewState {
+    /// Index of the first visible line.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Number of rendered lines in the document.
+    pub fn content_height(&self) -> usize {
+        self.content_height
+    }
+
+    /// Number of lines that fit in the view.
+    pub fn viewport_height(&self) -> usize {
+        self.viewport_height
+    }
+
+    /// The largest offset that still fills the view.
+    pub fn max_offset(&self) -> usize {
+        self.content_height.saturating_sub(self.viewport_height)
+    }
+
+    /// Whether the last line of the document is visible.
+    pub fn is_at_bottom(&self) -> bool {
+        self.offset >= self.max_offset()
+    }
+
+    /// Record the heights of a layout and clamp the offset to them. Called by
+    /// [`MarkdownView`] on every render.
+    pub fn set_heights(&mut self, content_height: usize, viewport_height: usize) {
+        self.content_height = content_height;
+        self.viewport_height = viewport_height;
+        self.offset = self.offset.min(self.max_offset());
+    }
+
+    /// Scroll so that `line` is the first visible line, as far as possible.
+    pub fn scroll_to(&mut self, line: usize) {
+        self.offset = line.min(self.max_offset());
+    }
+
+    /// Scroll just enough to show `line`, centering it if it was off screen.
+    pub fn reveal(&mut self, line: usize) {
+        if line < self.offset || line >= self.offset + self.viewport_height {
+            self.scroll_to(line.saturating_sub(self.viewport_height / 2));
+        }
+    }
+
+    pub fn scroll_down(&mut self, lines: usize) {
+        self.scroll_to(self.offset.saturating_add(lines));
+    }
+
+    pub fn scroll_up(&mut self, lines: usize) {
+        self.scroll_to(self.offset.saturating_sub(lines));
+    }
+
+    pub fn half_page_down(&mut self) {
+        self.scroll_down((self.viewport_height / 2).max(1));
+    }
+
+    pub fn half_page_up(&mut self) {
+        self.scroll_up((self.viewport_height / 2).max(1));
+    }
+
+    pub fn page_down(&mut self) {
+        self.scroll_down(self.viewport_height.max(1));
+    }
+
+    pub fn page_up(&mut self) {
+        self.scroll_up(self.viewport_height.max(1));
+    }
+
+    pub fn scroll_to_top(&mut self) {
+        self.offset = 0;
+    }
+
+    pub fn scroll_to_bottom(&mut self) {
+        self.offset = self.max_offset();
+    }
+}
+
+impl StatefulWidget for MarkdownView {
+    type State = MarkdownViewState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let text_area = if self.scrollbar {
+            Rect {
+                width: area.width.saturating_sub(1),
+                ..area
+            }
+        } else {
+            area
+        };
+
+        let theme = self.renderer.theme.clone();
+        let hyperlinks = self.renderer.link_display == LinkDisplay::Hyperlink;
+        let output = self.renderer.width(text_area.width).render_output(&self.input);
+        let mut lines = output.lines;
+        if let Some(search) = &self.search {
+            search.highlight(&mut lines, &theme);
+        }
+        links::highlight_focused(&mut lines, &output.links, state.focused_link, &theme);
+        render_lines(&lines, self.scrollbar, area, text_area, buf, state);
+        if hyperlinks {
+            links::write_hyperlinks(buf, text_area, state.offset(), &output.links);
+        }
+    }
+}
+
+/// Draw the visible slice of `lines` into `text_area` and, if enabled, a
+/// scrollbar along the right edge of `area`.
+pub(crate) fn render_lines(
+    lines: &[Line<'_>],
+    scrollbar: bool,
+    area: Rect,
+    text_area: Rect,
+    buf: &mut Buffer,
+    state: &mut MarkdownViewState,
+) {
+    state.set_heights(lines.len(), usize::from(area.height));
+    state.area = text_area;
+    let visible = lines.iter().skip(state.offset).take(state.viewport_height);
+    for (y, line) in (text_area.y..text_area.bottom()).zip(visible) {
+        buf.set_line(text_area.x, y, line, text_area.width);
+    }
+
+    if scrollbar && state.max_offset() > 0 {
+        let mut scrollbar_state = ScrollbarState::new(state.max_offset())
+            .position(state.offset)
+            .viewport_content_length(state.viewport_height);
+        Scrollbar::new(ScrollbarOrientation::VerticalRight).render(
+            area,
+            buf,
+            &mut scrollbar_state,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(content: usize, viewport: usize) -> MarkdownViewState {
+        let mut state = MarkdownViewState::default();
+        state.set_heights(content, viewport);
+        state
+    }
+
+    #[test]
+    fn scrolling_is_clamped_to_content() {
+        let mut state = state(100, 20);
+        state.scroll_down(500);
+        assert_eq!(state.offset(), 80);
+        assert!(state.is_at_bottom());
+        state.scroll_up(500);
+        assert_eq!(state.offset(), 0);
+    }
+
+    #[test]
+    fn pages_and_half_pages() {
+        let mut state = state(100, 20);
+        state.page_down();
+        assert_eq!(state.offset(), 20);
+        state.half_page_down();
+        assert_eq!(state.offset(), 30);
+        state.half_page_up();
+        state.page_up();
+        assert_eq!(state.offset(), 0);
+        state.scroll_to_bottom();
+        assert_eq!(state.offset(), 80);
+        state.scroll_to_top();
+        assert_eq!(state.offset(), 0);
+    }
+
+    #[test]
+    fn shrinking_content_clamps_offset() {
+        let mut state = state(100, 20);
+        state.scroll_to(70);
+        state.set_heights(50, 20);
+        assert_eq!(state.offset(), 30);
+    }
+
+    #[test]
+    fn renders_from_offset() {
+        let input = (1..=10).map(|n| format!("line {n}\n")).collect::<String>();
+        let view = || MarkdownView::new(&input).scrollbar(true);
+        let area = Rect::new(0, 0, 20, 3);
+        let mut buf = Buffer::empty(area);
+        let mut state = MarkdownViewState::default();
+
+        view().render(area, &mut buf, &mut state);
+        state.scroll_down(2);
+        view().render(area, &mut buf, &mut state);
+
+        let row: String = (0..19).map(|x| buf[(x, 0)].symbol()).collect();
+        assert_eq!(row.trim_end(), "line 3");
+        assert_eq!(state.content_height(), 10);
+        assert_ne!(buf[(19, 0)].symbol(), " ");
+    }
+}
@@ -1,25 +1,97 @@
 use ratatui::prelude::*;
-use ratatui::widgets::{Paragraph, Wrap};
+use ratatui::widgets::Paragraph;
+
+use crate::links::{self, LinkDisplay};
+use crate::wrap;
+use crate::{Renderer, Theme};
 
 /// A ratatui widget that renders markdown text.
 ///
-/// Convenience wrapper around [`crate::render()`] that implements [`Widget`]
-/// so you can render markdown directly into a frame area.
+/// Lays the document out at the width of the area it is drawn into, so
+/// wrapped list items and blockquotes keep their indents and bars. See
+/// [`Renderer::width`]. Code lines wider than the area wrap onto the next
+/// row rather than being cut off.
+///
+/// The input is parsed and laid out on every draw. To keep the layout
+/// between frames, or to scroll, use [`MarkdownDocument`] or
+/// [`MarkdownView`] instead.
+///
+/// [`MarkdownDocument`]: crate::MarkdownDocument
+/// [`MarkdownView`]: crate::MarkdownView
 pub struct MarkdownWidget {
-    lines: Vec<Line<'static>>,
+    input: String,
+    renderer: Renderer,
 }
 
 impl MarkdownWidget {
     pub fn new(input: &str) -> Self {
+        Self::with_renderer(input, Renderer::new())
+    }
+
+    /// Like [`MarkdownWidget::new`], but styled with a custom [`Theme`].
+    pub fn with_theme(input: &str, theme: &Theme) -> Self {
+        Self::with_renderer(input, Renderer::new().theme(theme.clone()))
+    }
+
+    /// Render `input` with a fully configured [`Renderer`].
+    pub fn with_renderer(input: &str, renderer: Renderer) -> Self {
         Self {
-            lines: crate::render(input),
+            input: input.to_string(),
+            renderer,
         }
     }
 }
 
 impl Widget for MarkdownWidget {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let paragraph = Paragraph::new(self.lines).wrap(Wrap { trim: false });
-        paragraph.render(area, buf);
+        let hyperlinks = self.renderer.link_display == LinkDisplay::Hyperlink;
+        let mut output = self.renderer.width(area.width).render_output(&self.input);
+        let rows = fold_wide_lines(&mut output.lines, usize::from(area.width));
+        Paragraph::new(output.lines).render(area, buf);
+        if hyperlinks {
+            for position in output.links.iter_mut().flat_map(|l| &mut l.positions) {
+                position.line = rows[position.line];
+            }
+            links::write_hyperlinks(buf, area, 0, &output.links);
+        }
+    }
+}
+
+/// Wrap lines wider than `width`, which only code blocks produce, onto
+/// extra rows. Returns the row each original line now starts on.
+fn fold_wide_lines(lines: &mut Vec<Line<'static>>, width: usize) -> Vec<usize> {
+    let mut rows = Vec::with_capacity(lines.len());
+    let mut folded = Vec::with_capacity(lines.len());
+    for line in lines.drain(..) {
+        rows.push(folded.len());
+        if line.width() <= width {
+            folded.push(line);
+            continue;
+        }
+        let style = line.style;
+        for spans in wrap::wrap_spans(&line.spans, width, width) {
+            folded.push(Line::from(spans).style(style));
+        }
+    }
+    *lines = folded;
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(buf: &Buffer, y: u16) -> String {
+        (0..buf.area.width).map(|x| buf[(x, y)].symbol()).collect()
+    }
+
+    #[test]
+    fn wide_code_wraps_instead_of_being_cut() {
+        let area = Rect::new(0, 0, 12, 4);
+        let mut buf = Buffer::empty(area);
+        MarkdownWidget::new("```\nlet alpha = beta;\n```").render(area, &mut buf);
+        assert_eq!(row(&buf, 0), "    let     ");
+        assert_eq!(row(&buf, 1), "alpha =     ");
+        assert_eq!(row(&buf, 2), "beta;       ");
     }
 }
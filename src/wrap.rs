@@ -0,0 +1,201 @@
+use ratatui::prelude::*;
+use unicode_width::UnicodeWidthChar;
+
+/// A run of text that is either all whitespace or contains none, possibly
+/// made of several differently styled pieces (e.g. `**bold**text`). Each
+/// piece carries the tag of the span it came from.
+struct Piece<T> {
+    segments: Vec<(String, Style, T)>,
+    width: usize,
+    is_space: bool,
+}
+
+/// Display width of `text` in terminal columns.
+pub(crate) fn text_width(text: &str) -> usize {
+    text.chars().map(|c| c.width().unwrap_or(0)).sum()
+}
+
+/// Word-wrap `spans` into lines, the first at most `first_width` columns
+/// wide and the rest at most `rest_width`. Whitespace at a break is dropped;
+/// words longer than a whole line are split between characters.
+pub(crate) fn wrap_spans(
+    spans: &[Span<'static>],
+    first_width: usize,
+    rest_width: usize,
+) -> Vec<Vec<Span<'static>>> {
+    let tagged: Vec<(Span<'static>, ())> = spans.iter().map(|s| (s.clone(), ())).collect();
+    wrap_tagged(&tagged, first_width, rest_width)
+        .into_iter()
+        .map(|line| line.into_iter().map(|(span, _)| span).collect())
+        .collect()
+}
+
+/// Like [`wrap_spans`], but each span carries a tag (such as the link it
+/// belongs to) that is kept on every piece of it in the output. Adjacent
+/// pieces are only merged when both style and tag match.
+pub(crate) fn wrap_tagged<T: Copy + PartialEq>(
+    spans: &[(Span<'static>, T)],
+    first_width: usize,
+    rest_width: usize,
+) -> Vec<Vec<(Span<'static>, T)>> {
+    let mut lines: Vec<Vec<(String, Style, T)>> = vec![Vec::new()];
+    let mut used = 0;
+    let mut limit = first_width.max(1);
+    let mut pending_space: Option<Piece<T>> = None;
+
+    for piece in pieces(spans) {
+        if piece.is_space {
+            if used > 0 {
+                pending_space = Some(piece);
+            } else if lines.len() == 1 {
+                // Keep deliberate leading whitespace on the first line.
+                used += piece.width;
+                lines[0].extend(piece.segments);
+            }
+            continue;
+        }
+
+        let space_width = pending_space.as_ref().map_or(0, |s| s.width);
+        if used > 0 && used + space_width + piece.width > limit {
+            lines.push(Vec::new());
+            used = 0;
+            limit = rest_width.max(1);
+            pending_space = None;
+        }
+        if let Some(space) = pending_space.take() {
+            used += space.width;
+            push_segments(&mut lines, space.segments);
+        }
+
+        if piece.width <= limit - used {
+            used += piece.width;
+            push_segments(&mut lines, piece.segments);
+            continue;
+        }
+
+        // Too long for any line: break between characters.
+        for (text, style, tag) in piece.segments {
+            for c in text.chars() {
+                let w = c.width().unwrap_or(0);
+                if used > 0 && used + w > limit {
+                    lines.push(Vec::new());
+                    used = 0;
+                    limit = rest_width.max(1);
+                }
+                used += w;
+                push_segments(&mut lines, vec![(c.to_string(), style, tag)]);
+            }
+        }
+    }
+
+    lines.into_iter().map(merge_segments).collect()
+}
+
+/// Split spans into alternating whitespace and word pieces.
+fn pieces<T: Copy>(spans: &[(Span<'static>, T)]) -> Vec<Piece<T>> {
+    let mut pieces: Vec<Piece<T>> = Vec::new();
+    for (span, tag) in spans {
+        let mut rest = span.content.as_ref();
+        while let Some(c) = rest.chars().next() {
+            let is_space = c.is_whitespace();
+            let end = rest
+                .find(|ch: char| ch.is_whitespace() != is_space)
+                .unwrap_or(rest.len());
+            let (text, tail) = rest.split_at(end);
+            rest = tail;
+
+            let width = text_width(text);
+            match pieces.last_mut() {
+                Some(last) if last.is_space == is_space => {
+                    last.segments.push((text.to_string(), span.style, *tag));
+                    last.width += width;
+                }
+                _ => pieces.push(Piece {
+                    segments: vec![(text.to_string(), span.style, *tag)],
+                    width,
+                    is_space,
+                }),
+            }
+        }
+    }
+    pieces
+}
+
+fn push_segments<T>(lines: &mut [Vec<(String, Style, T)>], segments: Vec<(String, Style, T)>) {
+    if let Some(line) = lines.last_mut() {
+        line.extend(segments);
+    }
+}
+
+/// Join adjacent segments that share a style and tag into single spans.
+fn merge_segments<T: PartialEq>(segments: Vec<(String, Style, T)>) -> Vec<(Span<'static>, T)> {
+    let mut spans: Vec<(Span<'static>, T)> = Vec::new();
+    for (text, style, tag) in segments {
+        match spans.last_mut() {
+            Some((last, last_tag)) if last.style == style && *last_tag == tag => {
+                last.content.to_mut().push_str(&text)
+            }
+            _ => spans.push((Span::styled(text, style), tag)),
+        }
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(lines: &[Vec<Span<'static>>]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|l| l.iter().map(|s| s.content.as_ref()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn wraps_at_word_boundaries() {
+        let spans = [Span::raw("the quick brown fox jumps")];
+        let lines = wrap_spans(&spans, 10, 10);
+        assert_eq!(texts(&lines), vec!["the quick", "brown fox", "jumps"]);
+    }
+
+    #[test]
+    fn continuation_width_differs_from_first() {
+        let spans = [Span::raw("aaa bbb ccc ddd")];
+        let lines = wrap_spans(&spans, 3, 7);
+        assert_eq!(texts(&lines), vec!["aaa", "bbb ccc", "ddd"]);
+    }
+
+    #[test]
+    fn long_words_are_split() {
+        let lines = wrap_spans(&[Span::raw("abcdefgh")], 3, 3);
+        assert_eq!(texts(&lines), vec!["abc", "def", "gh"]);
+    }
+
+    #[test]
+    fn styles_survive_wrapping() {
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        let spans = [Span::raw("one "), Span::styled("two three", bold)];
+        let lines = wrap_spans(&spans, 8, 8);
+        assert_eq!(texts(&lines), vec!["one two", "three"]);
+        assert_eq!(lines[0][1].style, bold);
+        assert_eq!(lines[1][0].style, bold);
+    }
+
+    #[test]
+    fn tags_follow_their_spans() {
+        let spans = [(Span::raw("see "), None), (Span::raw("the docs"), Some(0))];
+        let lines = wrap_tagged(&spans, 7, 7);
+        let tags: Vec<Vec<(&str, Option<usize>)>> = lines
+            .iter()
+            .map(|l| l.iter().map(|(s, t)| (s.content.as_ref(), *t)).collect())
+            .collect();
+        assert_eq!(tags, vec![vec![("see ", None), ("the", Some(0))], vec![("docs", Some(0))]]);
+    }
+
+    #[test]
+    fn wide_characters_count_double() {
+        let lines = wrap_spans(&[Span::raw("日本語 テキスト")], 6, 6);
+        assert_eq!(texts(&lines), vec!["日本語", "テキス", "ト"]);
+    }
+}